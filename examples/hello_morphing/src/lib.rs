@@ -4,13 +4,14 @@ use morphing_core::scene::SceneFilter;
 use morphing_core::timeline::Supervisor;
 use morphing_gizmos::mobjects::shape::Rect;
 
-use morphing_core::{chapter, scene, GetField, Supervisor};
+use morphing_core::{chapter, fp, scene, Supervisor};
 
 #[scene]
 fn demo_scene(sv: &Supervisor<'_>) {
     sv.wait(1.0);
-    let mobject = sv.spawn(Rect(nalgebra::Vector2::new(1.0, 1.0)));
+    let mut mobject = sv.spawn(Rect(nalgebra::Vector2::new(1.0, 1.0)));
     sv.wait(6.0);
+    sv.animate(&mut mobject, fp!(transform.scale), 2.0, 1.0);
     drop(mobject);
     sv.wait(12.0);
 }
@@ -75,18 +76,24 @@ struct TriangleVertex {
 struct TriangleLifecycle {}
 
 impl Lifecycle for TriangleLifecycle {
-    // type Signal = f32;
-    // type Resource = (bool,);
+    type Signal = f32;
+    type Resource = (bool,);
 
-    fn setup(&self) -> Resource {
+    fn setup(&self, _device: &wgpu::Device, _queue: &wgpu::Queue) -> Self::Resource {
         (false,)
     }
 
-    fn prepare(&self, signal: Signal, resource: &mut Resource) {
+    fn prepare(
+        &self,
+        signal: Self::Signal,
+        resource: &mut Self::Resource,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) {
         resource.0 = (signal as i32) % 2 == 1;
     }
 
-    fn render(&self, resource: &Resource) {
+    fn render(&self, resource: &Self::Resource, _render_pass: &mut wgpu::RenderPass<'_>) {
         if resource.0 {}
     }
 }
@@ -2,11 +2,14 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 use std::str::FromStr;
 
+use morphing_core::Interpolate;
+use palette::FromColor;
+use palette::Mix;
 use palette::WithAlpha;
 use strum::EnumProperty;
 use strum::VariantArray;
 
-#[derive(Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Color(palette::Srgba<f32>);
 
 impl Color {
@@ -21,6 +24,40 @@ impl Color {
     pub fn with_alpha(self, alpha: f32) -> Self {
         Self(self.0.with_alpha(alpha))
     }
+
+    pub fn interpolate_in(&self, other: &Self, alpha: f32, color_space: ColorSpace) -> Self {
+        match color_space {
+            ColorSpace::Srgb => self.mix_in::<palette::Srgba>(other, alpha),
+            ColorSpace::LinearSrgb => self.mix_in::<palette::LinSrgba>(other, alpha),
+            ColorSpace::Oklab => self.mix_in::<palette::Oklaba>(other, alpha),
+            ColorSpace::Hsl => self.mix_in::<palette::Hsla>(other, alpha),
+        }
+    }
+
+    fn mix_in<C>(&self, other: &Self, alpha: f32) -> Self
+    where
+        C: FromColor<palette::Srgba> + Mix<Scalar = f32>,
+        palette::Srgba: FromColor<C>,
+    {
+        palette::Srgba::from_color(C::from_color(self.0).mix(C::from_color(other.0), alpha)).into()
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        self.interpolate_in(other, alpha, ColorSpace::default())
+    }
+}
+
+// Color space in which two colors are blended.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    Srgb,
+    #[default]
+    LinearSrgb,
+    Oklab,
+    Hsl,
 }
 
 impl Deref for Color {
@@ -50,9 +87,9 @@ impl FromStr for Color {
     type Err = palette::rgb::FromHexError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(palette::Srgba::from_str(s)
-            .or(palette::Srgb::from_str(s).map(palette::Srgba::from))?
-            .into())
+        let srgba = palette::Srgba::<u8>::from_str(s)
+            .or_else(|_| palette::Srgb::<u8>::from_str(s).map(|srgb| srgb.with_alpha(u8::MAX)))?;
+        Ok(Self(srgba.into_format()))
     }
 }
 
@@ -198,3 +235,32 @@ impl From<Palette> for Color {
         color.get_str("hex").unwrap().parse().unwrap()
     }
 }
+
+#[cfg(test)]
+mod color_tests {
+    use morphing_core::Interpolate;
+
+    use super::Color;
+    use super::ColorSpace;
+
+    #[test]
+    fn test_color_spaces() {
+        let black = Color::from(palette::Srgba::new(0.0, 0.0, 0.0, 1.0));
+        let white = Color::max();
+        for color_space in [
+            ColorSpace::Srgb,
+            ColorSpace::LinearSrgb,
+            ColorSpace::Oklab,
+            ColorSpace::Hsl,
+        ] {
+            assert_eq!(black.interpolate_in(&white, 0.0, color_space), black);
+            assert!((black.interpolate_in(&white, 1.0, color_space).red - 1.0).abs() < 1e-5);
+        }
+        // Halfway in linear light is brighter than halfway between the encoded values.
+        let srgb = black.interpolate_in(&white, 0.5, ColorSpace::Srgb);
+        let linear_srgb = black.interpolate_in(&white, 0.5, ColorSpace::LinearSrgb);
+        assert!((srgb.red - 0.5).abs() < 1e-5);
+        assert!((linear_srgb.red - 0.7354).abs() < 1e-3);
+        assert_eq!(black.interpolate(&white, 0.5), linear_srgb);
+    }
+}
@@ -1,7 +1,22 @@
+use morphing_core::FieldIndex;
+use morphing_core::Interpolate;
+
 use super::paint::Paint;
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, PartialEq, FieldIndex, Interpolate, serde::Deserialize, serde::Serialize,
+)]
 pub struct Fill {
+    #[interpolate(step)]
     pub options: lyon::tessellation::FillOptions,
     pub paint: Paint,
 }
+
+impl Fill {
+    pub fn faded(&self, opacity: f32) -> Self {
+        Self {
+            options: self.options,
+            paint: self.paint.faded(opacity),
+        }
+    }
+}
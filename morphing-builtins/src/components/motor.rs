@@ -1,3 +1,8 @@
+use geometric_algebra::GeometricProduct;
+use geometric_algebra::Inverse;
+use geometric_algebra::Powf;
+use morphing_core::Interpolate;
+
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(from = "nalgebra::Vector4<f32>", into = "nalgebra::Vector4<f32>")]
 pub struct Motor2D(pub geometric_algebra::ppga2d::Motor);
@@ -10,15 +15,35 @@ impl From<nalgebra::Vector4<f32>> for Motor2D {
     }
 }
 
+// The motor is a union, which does not derive `PartialEq`.
+impl PartialEq for Motor2D {
+    fn eq(&self, other: &Self) -> bool {
+        (0..4).all(|index| self.0[index] == other.0[index])
+    }
+}
+
 impl From<Motor2D> for nalgebra::Vector4<f32> {
     fn from(Motor2D(m): Motor2D) -> Self {
         nalgebra::Vector4::new(m[0], m[1], m[2], m[3])
     }
 }
 
+// Moves along the geodesic from `self` to `other`, i.e. `self * exp(alpha * ln(self^-1 * other))`.
+// The relative motor is flipped onto the hemisphere of positive scalar part
+// so that the shorter of the two equivalent paths is taken.
+impl Interpolate for Motor2D {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        let mut delta = self.0.inverse().geometric_product(other.0);
+        if delta[0] < 0.0 {
+            delta = -delta;
+        }
+        Motor2D(self.0.geometric_product(delta.powf(alpha)))
+    }
+}
+
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(from = "nalgebra::Matrix4x2<f32>", into = "nalgebra::Matrix4x2<f32>")]
-pub struct Motor3D(pub geometric_algebra::ppga3d::Motor); // transpose?
+pub struct Motor3D(pub geometric_algebra::ppga3d::Motor);
 
 impl From<nalgebra::Matrix4x2<f32>> for Motor3D {
     fn from(m: nalgebra::Matrix4x2<f32>) -> Self {
@@ -28,8 +53,42 @@ impl From<nalgebra::Matrix4x2<f32>> for Motor3D {
     }
 }
 
+impl PartialEq for Motor3D {
+    fn eq(&self, other: &Self) -> bool {
+        (0..8).all(|index| self.0[index] == other.0[index])
+    }
+}
+
 impl From<Motor3D> for nalgebra::Matrix4x2<f32> {
     fn from(Motor3D(m): Motor3D) -> Self {
-        nalgebra::Matrix4x2::new(m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7])
+        // Column-major, as read back by the conversion above. The columns are the groups of the motor,
+        // `(1, e23, -e13, e12)` and `(e0123, e01, e02, e03)`, as `apply_motor_3d` in the shader expects.
+        nalgebra::Matrix4x2::from_column_slice(&[m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7]])
+    }
+}
+
+impl Interpolate for Motor3D {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        let mut delta = self.0.inverse().geometric_product(other.0);
+        if delta[0] < 0.0 {
+            delta = -delta;
+        }
+        Motor3D(self.0.geometric_product(delta.powf(alpha)))
+    }
+}
+
+#[cfg(test)]
+mod motor_tests {
+    use super::*;
+
+    #[test]
+    fn test_motor_3d_matrix() {
+        let motor = Motor3D(geometric_algebra::ppga3d::Motor::new(
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0,
+        ));
+        let matrix = nalgebra::Matrix4x2::from(motor.clone());
+        assert_eq!(matrix.column(0), nalgebra::Vector4::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(matrix.column(1), nalgebra::Vector4::new(5.0, 6.0, 7.0, 8.0));
+        assert_eq!(Motor3D::from(matrix), motor);
     }
 }
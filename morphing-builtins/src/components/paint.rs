@@ -1,97 +1,154 @@
+use morphing_core::Component;
+use morphing_core::FieldIndex;
+use morphing_core::Interpolate;
+use morphing_core::wgpu_shader_types;
+use morphing_core::wgpu_struct;
+
 use super::color::Color;
+use super::color::ColorSpace;
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, PartialEq, FieldIndex, Interpolate, serde::Deserialize, serde::Serialize,
+)]
 pub struct Paint {
     pub color: Color,
+    #[interpolate(with = "interpolate_gradients")]
     pub gradients: Vec<Gradient>,
 }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, PartialEq, FieldIndex, Interpolate, serde::Deserialize, serde::Serialize,
+)]
 pub struct Gradient {
     pub from_position: nalgebra::Vector2<f32>,
     pub to_position: nalgebra::Vector2<f32>,
     pub radius_slope: f32,
     pub radius_quotient: f32,
+    #[interpolate(with = "interpolate_stops")]
     pub radial_stops: Vec<(f32, Color)>,
+    #[interpolate(with = "interpolate_stops")]
     pub angular_stops: Vec<(f32, Color)>,
 }
 
-// pub struct PaintBuffers {
-//     color_uniform: wgpu::Buffer,
-//     gradients_storage: wgpu::Buffer,
-//     radial_stops_storage: wgpu::Buffer,
-//     angular_stops_storage: wgpu::Buffer,
-// }
+impl Paint {
+    // Scales the paint towards transparent black, which vanishes under additive blending.
+    pub fn faded(&self, opacity: f32) -> Self {
+        Self {
+            color: Color::min().interpolate_in(&self.color, opacity, ColorSpace::Srgb),
+            gradients: self.gradients.clone(),
+        }
+    }
+}
 
-// pub struct PaintShaderTypes {
-//     color_uniform: ColorUniform,
-//     gradients_storage: Vec<GradientStorage>,
-//     radial_stops_storage: Vec<GradientStopStorage>,
-//     angular_stops_storage: Vec<GradientStopStorage>,
-// }
+impl Gradient {
+    // Samples stops the same way as the shader does.
+    // A gradient without stops does not tint the paint.
+    pub fn sample_stops(stops: &[(f32, Color)], alpha: f32) -> Color {
+        let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+            return Color::max();
+        };
+        if alpha < first.0 {
+            return first.1;
+        }
+        if alpha >= last.0 {
+            return last.1;
+        }
+        let end = stops.partition_point(|(stop_alpha, _)| *stop_alpha <= alpha);
+        let (start_alpha, start_color) = stops[end - 1];
+        let (end_alpha, end_color) = stops[end];
+        start_color.interpolate_in(
+            &end_color,
+            (alpha - start_alpha) / (end_alpha - start_alpha),
+            ColorSpace::Srgb,
+        )
+    }
 
-struct ColorUniform {
-    color: nalgebra::Vector4<f32>,
+    fn without_stops(&self) -> Self {
+        Self {
+            radial_stops: Vec::new(),
+            angular_stops: Vec::new(),
+            ..self.clone()
+        }
+    }
 }
 
-struct GradientStorage {
-    from_position: nalgebra::Vector2<f32>,
-    to_position: nalgebra::Vector2<f32>,
-    radius_slope: f32,
-    radius_quotient: f32,
-    radial_stops_range: nalgebra::Vector2<u32>,
-    angular_stops_range: nalgebra::Vector2<u32>,
+// Gradients without a counterpart are paired with a copy of themselves without stops,
+// so they fade in or out of the paint.
+fn interpolate_gradients(gradients: &[Gradient], other: &[Gradient], alpha: f32) -> Vec<Gradient> {
+    (0..gradients.len().max(other.len()))
+        .map(|i| match (gradients.get(i), other.get(i)) {
+            (Some(gradient), Some(other_gradient)) => gradient.interpolate(other_gradient, alpha),
+            (Some(gradient), None) => gradient.interpolate(&gradient.without_stops(), alpha),
+            (None, Some(other_gradient)) => other_gradient
+                .without_stops()
+                .interpolate(other_gradient, alpha),
+            (None, None) => unreachable!(),
+        })
+        .collect()
 }
 
-struct GradientStopStorage {
+// Stop lists of different lengths are first resampled at the union of their stop positions.
+fn interpolate_stops(
+    stops: &[(f32, Color)],
+    other: &[(f32, Color)],
     alpha: f32,
-    color: nalgebra::Vector4<f32>,
+) -> Vec<(f32, Color)> {
+    if stops.len() == other.len() {
+        return stops
+            .iter()
+            .zip(other)
+            .map(|(stop, other_stop)| stop.interpolate(other_stop, alpha))
+            .collect();
+    }
+    let mut stop_alphas: Vec<f32> = stops
+        .iter()
+        .chain(other)
+        .map(|(stop_alpha, _)| *stop_alpha)
+        .collect();
+    stop_alphas.sort_by(f32::total_cmp);
+    stop_alphas.dedup();
+    stop_alphas
+        .into_iter()
+        .map(|stop_alpha| {
+            (
+                stop_alpha,
+                Gradient::sample_stops(stops, stop_alpha)
+                    .interpolate(&Gradient::sample_stops(other, stop_alpha), alpha),
+            )
+        })
+        .collect()
 }
 
-// wgpu_struct! {
-//     struct ColorUniform {
-//         color: vec4<f32>,
-//     }
-
-//     struct GradientStorage {
-//         from_position: vec2<f32>,
-//         to_position: vec2<f32>,
-//         radius_slope: f32,
-//         radius_quotient: f32,
-//         radial_stops_range: vec2<u32>,
-//         angular_stops_range: vec2<u32>,
-//     }
-
-//     struct GradientStopStorage {
-//         alpha: f32,
-//         color: vec4<f32>,
-//     }
-// }
+wgpu_struct! {
+    struct ColorUniform {
+        color: vec4<f32>,
+    }
 
-// wgpu_shader_types! {
-//     pub struct PaintShaderTypes {
-//         pub(FRAGMENT) @binding(0) var<uniform> u_paint: ColorUniform,
-//         pub(FRAGMENT) @binding(1) var<storage> s_gradients: array<GradientStorage>,
-//         pub(FRAGMENT) @binding(2) var<storage> s_radial_stops: array<GradientStopStorage>,
-//         pub(FRAGMENT) @binding(3) var<storage> s_angular_stops: array<GradientStopStorage>,
-//     }
-// }
+    struct GradientStorage {
+        from_position: vec2<f32>,
+        to_position: vec2<f32>,
+        radius_slope: f32,
+        radius_quotient: f32,
+        radial_stops_range: vec2<u32>,
+        angular_stops_range: vec2<u32>,
+    }
 
-pub struct PaintBuffers {
-    color_uniform: ::wgpu::Buffer,
-    gradients_storage: ::wgpu::Buffer,
-    radial_stops_storage: ::wgpu::Buffer,
-    angular_stops_storage: ::wgpu::Buffer,
+    struct GradientStopStorage {
+        alpha: f32,
+        color: vec4<f32>,
+    }
 }
 
-pub struct PaintShaderTypes {
-    u_paint: ColorUniform,
-    s_gradients: Vec<GradientStorage>,
-    s_radial_stops: Vec<GradientStopStorage>,
-    s_angular_stops: Vec<GradientStopStorage>,
+wgpu_shader_types! {
+    pub struct PaintShaderTypes {
+        pub(FRAGMENT) @binding(0) var<uniform> u_color: ColorUniform,
+        pub(FRAGMENT) @binding(1) var<storage> s_gradients: array<GradientStorage>,
+        pub(FRAGMENT) @binding(2) var<storage> s_radial_stops: array<GradientStopStorage>,
+        pub(FRAGMENT) @binding(3) var<storage> s_angular_stops: array<GradientStopStorage>,
+    }
 }
 
-impl ::morphing_core::traits::Component for Paint {
+impl Component for Paint {
     type ShaderTypes = PaintShaderTypes;
 
     fn to_shader_types(&self) -> Self::ShaderTypes {
@@ -120,13 +177,13 @@ impl ::morphing_core::traits::Component for Paint {
                     *angular_stops_len = angular_stops_range.end;
                     radial_stops_storage.extend(radial_stops.iter().map(|&(alpha, color)| {
                         GradientStopStorage {
-                            alpha: alpha,
+                            alpha,
                             color: color.into(),
                         }
                     }));
                     angular_stops_storage.extend(angular_stops.iter().map(|&(alpha, color)| {
                         GradientStopStorage {
-                            alpha: alpha,
+                            alpha,
                             color: color.into(),
                         }
                     }));
@@ -148,243 +205,69 @@ impl ::morphing_core::traits::Component for Paint {
             )
             .collect();
         PaintShaderTypes {
-            color_uniform: ColorUniform {
+            u_color: ColorUniform {
                 color: self.color.into(),
             },
-            gradients_storage,
-            radial_stops_storage,
-            angular_stops_storage,
+            s_gradients: gradients_storage,
+            s_radial_stops: radial_stops_storage,
+            s_angular_stops: angular_stops_storage,
         }
     }
 }
 
-static PAINT_BIND_GROUP_LAYOUT: ::std::sync::OnceLock<::wgpu::BindGroupLayout> =
-    ::std::sync::OnceLock::new();
+#[cfg(test)]
+mod paint_tests {
+    use super::Color;
+    use super::interpolate_stops;
 
-impl ::morphing_core::traits::ComponentShaderTypes for PaintShaderTypes {
-    type Buffers = PaintBuffers;
-
-    fn bind_group_layout(device: &::wgpu::Device) -> &'static ::wgpu::BindGroupLayout {
-        PAINT_BIND_GROUP_LAYOUT.get_or_init(|| {
-            device.create_bind_group_layout(&::wgpu::BindGroupLayoutDescriptor {
-                label: None,
-                entries: &[
-                    // pub(FRAGMENT) @binding(0) var<uniform> u_paint: ColorUniform;
-                    ::wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: ::wgpu::ShaderStages::FRAGMENT,
-                        ty: ::wgpu::BindingType::Buffer {
-                            ty: ::wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: Some(
-                                <ColorUniform as ::encase::ShaderType>::min_size(),
-                            ),
-                        },
-                        count: None,
-                    },
-                    // pub(FRAGMENT) @binding(1) var<storage> s_gradients: array<GradientStorage>;
-                    ::wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: ::wgpu::ShaderStages::FRAGMENT,
-                        ty: ::wgpu::BindingType::Buffer {
-                            ty: ::wgpu::BufferBindingType::Storage { read_only: true },
-                            has_dynamic_offset: false,
-                            min_binding_size: Some(
-                                <GradientStorage as ::encase::ShaderType>::min_size(),
-                            ),
-                        },
-                        count: None,
-                    },
-                    // pub(FRAGMENT) @binding(2) var<storage> s_radial_stops: array<GradientStopStorage>;
-                    ::wgpu::BindGroupLayoutEntry {
-                        binding: 2,
-                        visibility: ::wgpu::ShaderStages::FRAGMENT,
-                        ty: ::wgpu::BindingType::Buffer {
-                            ty: ::wgpu::BufferBindingType::Storage { read_only: true },
-                            has_dynamic_offset: false,
-                            min_binding_size: Some(
-                                <Vec<GradientStopStorage> as ::encase::ShaderType>::min_size(),
-                            ),
-                        },
-                        count: None,
-                    },
-                    // pub(FRAGMENT) @binding(3) var<storage> s_angular_stops: array<GradientStopStorage>;
-                    ::wgpu::BindGroupLayoutEntry {
-                        binding: 3,
-                        visibility: ::wgpu::ShaderStages::FRAGMENT,
-                        ty: ::wgpu::BindingType::Buffer {
-                            ty: ::wgpu::BufferBindingType::Storage { read_only: true },
-                            has_dynamic_offset: false,
-                            min_binding_size: Some(
-                                <GradientStopStorage as ::encase::ShaderType>::min_size(),
-                            ),
-                        },
-                        count: None,
-                    },
-                ],
-            })
-        })
+    fn rgb(red: f32, green: f32, blue: f32) -> Color {
+        Color::from(palette::Srgba::new(red, green, blue, 1.0))
     }
 
-    fn bind_group_from_buffers(
-        device: &::wgpu::Device,
-        buffers: &Self::Buffers,
-    ) -> ::wgpu::BindGroup {
-        device.create_bind_group(&::wgpu::BindGroupDescriptor {
-            label: None,
-            layout: PaintShaderTypes::bind_group_layout(device),
-            entries: &[
-                ::wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: buffers.color_uniform.as_entire_binding(),
-                },
-                ::wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: buffers.gradients_storage.as_entire_binding(),
-                },
-                ::wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: buffers.radial_stops_storage.as_entire_binding(),
-                },
-                ::wgpu::BindGroupEntry {
-                    binding: 3,
-                    resource: buffers.angular_stops_storage.as_entire_binding(),
-                },
-            ],
-        })
-    }
-
-    fn new_buffers(&self, device: &::wgpu::Device) -> Self::Buffers {
-        PaintBuffers {
-            color_uniform: device.create_buffer(&::wgpu::BufferDescriptor {
-                label: None,
-                size: ::encase::ShaderType::size(&self.color_uniform).get(),
-                usage: ::wgpu::BufferUsages::UNIFORM | ::wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            }),
-            gradients_storage: device.create_buffer(&::wgpu::BufferDescriptor {
-                label: None,
-                size: ::encase::ShaderType::size(&self.gradients_storage).get(),
-                usage: ::wgpu::BufferUsages::STORAGE | ::wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            }),
-            radial_stops_storage: device.create_buffer(&::wgpu::BufferDescriptor {
-                label: None,
-                size: ::encase::ShaderType::size(&self.radial_stops_storage).get(),
-                usage: ::wgpu::BufferUsages::STORAGE | ::wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            }),
-            angular_stops_storage: device.create_buffer(&::wgpu::BufferDescriptor {
-                label: None,
-                size: ::encase::ShaderType::size(&self.angular_stops_storage).get(),
-                usage: ::wgpu::BufferUsages::STORAGE | ::wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            }),
+    fn assert_stops_eq(stops: &[(f32, Color)], expected: &[(f32, Color)]) {
+        assert_eq!(stops.len(), expected.len(), "{stops:?}");
+        for ((alpha, color), (expected_alpha, expected_color)) in stops.iter().zip(expected) {
+            assert_eq!(alpha, expected_alpha);
+            for (channel, expected_channel) in [
+                (color.red, expected_color.red),
+                (color.green, expected_color.green),
+                (color.blue, expected_color.blue),
+                (color.alpha, expected_color.alpha),
+            ] {
+                assert!((channel - expected_channel).abs() < 1e-5, "{stops:?}");
+            }
         }
     }
 
-    fn new_buffers_initialized(&self, device: &::wgpu::Device) -> Self::Buffers {
-        use wgpu::util::DeviceExt;
-        PaintBuffers {
-            color_uniform: {
-                let mut buffer = ::encase::UniformBuffer::new(Vec::<u8>::new());
-                buffer.write(&self.color_uniform).unwrap();
-                device.create_buffer_init(&::wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: buffer.as_ref(),
-                    usage: ::wgpu::BufferUsages::UNIFORM,
-                })
-            },
-            gradients_storage: {
-                let mut buffer = ::encase::DynamicStorageBuffer::new(Vec::<u8>::new());
-                buffer.write(&self.gradients_storage).unwrap();
-                device.create_buffer_init(&::wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: buffer.as_ref(),
-                    usage: ::wgpu::BufferUsages::STORAGE,
-                })
-            },
-            radial_stops_storage: {
-                let mut buffer = ::encase::DynamicStorageBuffer::new(Vec::<u8>::new());
-                buffer.write(&self.radial_stops_storage).unwrap();
-                device.create_buffer_init(&::wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: buffer.as_ref(),
-                    usage: ::wgpu::BufferUsages::STORAGE,
-                })
-            },
-            angular_stops_storage: {
-                let mut buffer = ::encase::DynamicStorageBuffer::new(Vec::<u8>::new());
-                buffer.write(&self.angular_stops_storage).unwrap();
-                device.create_buffer_init(&::wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: buffer.as_ref(),
-                    usage: ::wgpu::BufferUsages::STORAGE,
-                })
-            },
-        }
-    }
-
-    fn write_buffers(&self, queue: &::wgpu::Queue, buffers: &mut Self::Buffers) {
-        ::encase::internal::WriteInto::write_into(
-            &self.color_uniform,
-            &mut ::encase::internal::Writer::new(
-                &self.color_uniform,
-                &mut *queue
-                    .write_buffer_with(
-                        &buffers.color_uniform,
-                        0,
-                        ::encase::ShaderType::size(&self.color_uniform),
-                    )
-                    .unwrap(),
-                0,
-            )
-            .unwrap(),
-        );
-        ::encase::internal::WriteInto::write_into(
-            &self.gradients_storage,
-            &mut ::encase::internal::Writer::new(
-                &self.gradients_storage,
-                &mut *queue
-                    .write_buffer_with(
-                        &buffers.gradients_storage,
-                        0,
-                        ::encase::ShaderType::size(&self.gradients_storage),
-                    )
-                    .unwrap(),
-                0,
-            )
-            .unwrap(),
-        );
-        ::encase::internal::WriteInto::write_into(
-            &self.radial_stops_storage,
-            &mut ::encase::internal::Writer::new(
-                &self.radial_stops_storage,
-                &mut *queue
-                    .write_buffer_with(
-                        &buffers.radial_stops_storage,
-                        0,
-                        ::encase::ShaderType::size(&self.radial_stops_storage),
-                    )
-                    .unwrap(),
-                0,
-            )
-            .unwrap(),
+    #[test]
+    fn test_interpolate_stops() {
+        let stops = [(0.0, rgb(0.0, 0.0, 0.0)), (1.0, rgb(1.0, 1.0, 1.0))];
+        let other = [
+            (0.0, rgb(0.0, 0.0, 0.0)),
+            (0.5, rgb(1.0, 0.0, 0.0)),
+            (1.0, rgb(1.0, 1.0, 1.0)),
+        ];
+        // Both lists are resampled at 0, 0.5 and 1, where the first one is grey in sRGB.
+        assert_stops_eq(
+            &interpolate_stops(&stops, &other, 0.0),
+            &[
+                (0.0, rgb(0.0, 0.0, 0.0)),
+                (0.5, rgb(0.5, 0.5, 0.5)),
+                (1.0, rgb(1.0, 1.0, 1.0)),
+            ],
         );
-        ::encase::internal::WriteInto::write_into(
-            &self.angular_stops_storage,
-            &mut ::encase::internal::Writer::new(
-                &self.angular_stops_storage,
-                &mut *queue
-                    .write_buffer_with(
-                        &buffers.angular_stops_storage,
-                        0,
-                        ::encase::ShaderType::size(&self.angular_stops_storage),
-                    )
-                    .unwrap(),
-                0,
-            )
-            .unwrap(),
+        assert_stops_eq(&interpolate_stops(&stops, &other, 1.0), &other);
+        assert_stops_eq(&interpolate_stops(&other, &stops, 0.0), &other);
+
+        let halfway = interpolate_stops(&stops, &other, 0.5);
+        assert_eq!(halfway.len(), 3);
+        assert!(halfway[1].1.red > halfway[1].1.green);
+
+        // Lists of the same length are blended stop by stop.
+        let shifted = [(0.25, rgb(0.0, 0.0, 0.0)), (0.75, rgb(1.0, 1.0, 1.0))];
+        assert_stops_eq(
+            &interpolate_stops(&stops, &shifted, 0.5),
+            &[(0.125, rgb(0.0, 0.0, 0.0)), (0.875, rgb(1.0, 1.0, 1.0))],
         );
     }
 }
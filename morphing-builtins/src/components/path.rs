@@ -1,15 +1,15 @@
-use core::range::IterRangeFrom;
-use core::range::RangeFrom;
-use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 use itertools::Itertools;
+use morphing_core::Interpolate;
 
 use super::stroke::DashPattern;
 
 // #[derive(Clone)]
 // pub struct Path(pub lyon::path::Path);
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Path(Vec<bezier_rs::Subpath<ManipulatorGroupId>>);
 
 impl Path {
@@ -68,10 +68,7 @@ impl Path {
                 })
                 .collect_vec();
             alphas.rotate_right(1);
-            let alpha_period = alphas
-                .get_mut(0)
-                .map(|alpha| std::mem::take(alpha))
-                .unwrap_or_default();
+            let alpha_period = alphas.get_mut(0).map(std::mem::take).unwrap_or_default();
             (-(phase / alpha_period).ceil() as i32..((1.0 - phase) / alpha_period).ceil() as i32)
                 .map(move |i| i as f64 * alpha_period + phase)
                 .flat_map(move |alpha_offset| {
@@ -198,6 +195,151 @@ impl Path {
                 .collect_vec()
         }))
     }
+
+    // Brings both paths into the same structure, so that they can be blended pointwise:
+    // equally many subpaths, pairwise agreeing on closedness and on the number of segments,
+    // all of which are cubic Béziers.
+    // Missing subpaths are grown from the center of the path.
+    // A closed subpath paired with an open one is opened, keeping its closing segment explicit.
+    pub fn align_with(&self, other: &Self) -> (Self, Self) {
+        let center = self.center().or_else(|| other.center()).unwrap_or_default();
+        let other_center = other.center().or_else(|| self.center()).unwrap_or_default();
+        let (subpaths, other_subpaths) = (0..self.0.len().max(other.0.len()))
+            .map(|i| {
+                let subpath = self.0.get(i);
+                let other_subpath = other.0.get(i);
+                let closed = subpath.or(other_subpath).unwrap().closed
+                    && other_subpath.or(subpath).unwrap().closed;
+                let segments = cubic_segments(subpath, center);
+                let other_segments = cubic_segments(other_subpath, other_center);
+                // A closed subpath needs at least two segments, so a single loop is split in two.
+                let min_segments = if closed { 2 } else { 1 };
+                let len_segments = segments.len().max(other_segments.len()).max(min_segments);
+                (
+                    bezier_rs::Subpath::from_beziers(&subdivide(segments, len_segments), closed),
+                    bezier_rs::Subpath::from_beziers(
+                        &subdivide(other_segments, len_segments),
+                        closed,
+                    ),
+                )
+            })
+            .unzip();
+        (Self(subpaths), Self(other_subpaths))
+    }
+
+    fn center(&self) -> Option<glam::DVec2> {
+        self.bounding_box().map(|[min, max]| (min + max) / 2.0)
+    }
+}
+
+impl Interpolate for Path {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        let (path, other) = self.align_with(other);
+        Self::from_iter(
+            path.iter()
+                .zip(other.iter())
+                .map(|(subpath, other_subpath)| {
+                    let beziers = subpath
+                        .iter()
+                        .zip(other_subpath.iter())
+                        .map(|(bezier, other_bezier)| {
+                            interpolate_cubic(&bezier, &other_bezier, alpha)
+                        })
+                        .collect_vec();
+                    bezier_rs::Subpath::from_beziers(&beziers, subpath.closed)
+                }),
+        )
+    }
+}
+
+// A missing or empty subpath degenerates into a single segment sitting at `center`.
+fn cubic_segments(
+    subpath: Option<&bezier_rs::Subpath<ManipulatorGroupId>>,
+    center: glam::DVec2,
+) -> Vec<bezier_rs::Bezier> {
+    let point_segment = |point| bezier_rs::Bezier::from_cubic_dvec2(point, point, point, point);
+    match subpath {
+        Some(subpath) if subpath.len_segments() != 0 => subpath
+            .iter()
+            .map(|bezier| {
+                let bezier_rs::Bezier {
+                    start,
+                    end,
+                    handles,
+                } = bezier;
+                let (handle_start, handle_end) = match handles {
+                    bezier_rs::BezierHandles::Linear => {
+                        (start.lerp(end, 1.0 / 3.0), start.lerp(end, 2.0 / 3.0))
+                    }
+                    bezier_rs::BezierHandles::Quadratic { handle } => {
+                        (start.lerp(handle, 2.0 / 3.0), end.lerp(handle, 2.0 / 3.0))
+                    }
+                    bezier_rs::BezierHandles::Cubic {
+                        handle_start,
+                        handle_end,
+                    } => (handle_start, handle_end),
+                };
+                bezier_rs::Bezier::from_cubic_dvec2(start, handle_start, handle_end, end)
+            })
+            .collect(),
+        Some(subpath) if !subpath.is_empty() => vec![point_segment(subpath[0].anchor)],
+        _ => vec![point_segment(center)],
+    }
+}
+
+// Splits segments until there are `len_segments` of them.
+// Each extra piece goes to the segment whose pieces are currently the longest,
+// and every segment is then split evenly in parameter space.
+fn subdivide(segments: Vec<bezier_rs::Bezier>, len_segments: usize) -> Vec<bezier_rs::Bezier> {
+    let lengths = segments
+        .iter()
+        .map(|segment| segment.length(None))
+        .collect_vec();
+    let mut pieces = vec![1; segments.len()];
+    for _ in segments.len()..len_segments {
+        let i = (0..segments.len())
+            .max_by(|&i, &j| {
+                (lengths[i] / pieces[i] as f64).total_cmp(&(lengths[j] / pieces[j] as f64))
+            })
+            .unwrap();
+        pieces[i] += 1;
+    }
+    segments
+        .into_iter()
+        .zip(pieces)
+        .flat_map(|(mut segment, pieces)| {
+            let mut split_segments = Vec::with_capacity(pieces);
+            for remaining_pieces in (2..=pieces).rev() {
+                let [head, tail] =
+                    segment.split(bezier_rs::TValue::Parametric(1.0 / remaining_pieces as f64));
+                split_segments.push(head);
+                segment = tail;
+            }
+            split_segments.push(segment);
+            split_segments
+        })
+        .collect()
+}
+
+fn interpolate_cubic(
+    bezier: &bezier_rs::Bezier,
+    other: &bezier_rs::Bezier,
+    alpha: f32,
+) -> bezier_rs::Bezier {
+    fn cubic_points(bezier: &bezier_rs::Bezier) -> [[f64; 2]; 4] {
+        match bezier.handles {
+            bezier_rs::BezierHandles::Cubic {
+                handle_start,
+                handle_end,
+            } => [bezier.start, handle_start, handle_end, bezier.end].map(|point| point.to_array()),
+            _ => unreachable!(),
+        }
+    }
+
+    let [start, handle_start, handle_end, end] = cubic_points(bezier)
+        .interpolate(&cubic_points(other), alpha)
+        .map(glam::DVec2::from_array);
+    bezier_rs::Bezier::from_cubic_dvec2(start, handle_start, handle_end, end)
 }
 
 impl FromIterator<bezier_rs::Subpath<ManipulatorGroupId>> for Path {
@@ -211,6 +353,12 @@ pub struct PathBuilder {
     closed: bool,
 }
 
+impl Default for PathBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PathBuilder {
     pub fn new() -> Self {
         Self {
@@ -267,15 +415,110 @@ pub struct ManipulatorGroupId(usize);
 
 impl bezier_rs::Identifier for ManipulatorGroupId {
     fn new() -> Self {
-        Self(
-            MANIPULATOR_GROUP_ID_GENERATOR
-                .lock()
-                .unwrap()
-                .next()
-                .unwrap(),
-        )
+        Self(MANIPULATOR_GROUP_ID_COUNT.fetch_add(1, Ordering::Relaxed))
     }
 }
 
-static MANIPULATOR_GROUP_ID_GENERATOR: Mutex<IterRangeFrom<usize>> =
-    Mutex::new(RangeFrom { start: 0 }.into_iter());
+static MANIPULATOR_GROUP_ID_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(test)]
+mod path_tests {
+    use morphing_core::Interpolate;
+
+    use super::Path;
+
+    fn polygon(points: &[(f64, f64)]) -> Path {
+        let points = points
+            .iter()
+            .map(|&(x, y)| glam::DVec2::new(x, y))
+            .collect::<Vec<_>>();
+        let beziers = (0..points.len())
+            .map(|i| {
+                bezier_rs::Bezier::from_linear_dvec2(points[i], points[(i + 1) % points.len()])
+            })
+            .collect::<Vec<_>>();
+        Path::from_iter([bezier_rs::Subpath::from_beziers(&beziers, true)])
+    }
+
+    #[test]
+    fn test_align_with() {
+        let triangle = polygon(&[(0.0, 0.0), (2.0, 0.0), (0.0, 2.0)]);
+        let squares = Path::concat([
+            polygon(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]),
+            polygon(&[(2.0, 2.0), (3.0, 2.0), (3.0, 3.0), (2.0, 3.0)]),
+        ]);
+        let (aligned_triangle, aligned_squares) = triangle.align_with(&squares);
+        assert_eq!(aligned_triangle.iter().count(), 2);
+        assert_eq!(aligned_squares.iter().count(), 2);
+        for (subpath, other_subpath) in aligned_triangle.iter().zip(aligned_squares.iter()) {
+            assert_eq!(subpath.len_segments(), 4);
+            assert_eq!(other_subpath.len_segments(), 4);
+        }
+        assert_eq!(
+            triangle.interpolate(&squares, 0.0).bounding_box(),
+            triangle.bounding_box()
+        );
+    }
+
+    #[test]
+    fn test_align_with_closedness() {
+        let triangle = polygon(&[(0.0, 0.0), (2.0, 0.0), (0.0, 2.0)]);
+        let line = Path::from_iter([bezier_rs::Subpath::from_beziers(
+            &[bezier_rs::Bezier::from_linear_dvec2(
+                glam::DVec2::new(0.0, 0.0),
+                glam::DVec2::new(2.0, 2.0),
+            )],
+            false,
+        )]);
+        // A closed subpath made of a single segment, which `bezier_rs` only lets through
+        // when deserializing or assigning `closed`.
+        let mut teardrop = bezier_rs::Subpath::new(
+            vec![bezier_rs::ManipulatorGroup::new(
+                glam::DVec2::new(1.0, 1.0),
+                Some(glam::DVec2::new(2.0, 2.0)),
+                Some(glam::DVec2::new(0.0, 2.0)),
+            )],
+            false,
+        );
+        teardrop.closed = true;
+        let teardrop = Path(vec![teardrop]);
+        assert_eq!(teardrop.iter().next().unwrap().len_segments(), 1);
+
+        // The closed subpath is opened, keeping its closing segment.
+        let (aligned_triangle, aligned_line) = triangle.align_with(&line);
+        let (subpath, other_subpath) = (
+            aligned_triangle.iter().next().unwrap(),
+            aligned_line.iter().next().unwrap(),
+        );
+        assert!(!subpath.closed && !other_subpath.closed);
+        assert_eq!(subpath.len_segments(), 3);
+        assert_eq!(other_subpath.len_segments(), 3);
+        assert_eq!(
+            triangle.interpolate(&line, 0.0).bounding_box(),
+            triangle.bounding_box()
+        );
+        assert_eq!(
+            line.interpolate(&triangle, 0.0).bounding_box(),
+            line.bounding_box()
+        );
+
+        // Closed subpaths of a single segment, or of none, are split in two.
+        let empty = Path::from_iter([]);
+        for (path, other) in [(&teardrop, &teardrop), (&teardrop, &empty)] {
+            let (aligned_path, aligned_other) = path.align_with(other);
+            for subpath in aligned_path.iter().chain(aligned_other.iter()) {
+                assert!(subpath.closed);
+                assert_eq!(subpath.len_segments(), 2);
+            }
+            assert!(path.interpolate(other, 0.5).iter().next().unwrap().closed);
+        }
+        assert!(
+            !teardrop
+                .interpolate(&line, 0.5)
+                .iter()
+                .next()
+                .unwrap()
+                .closed
+        );
+    }
+}
@@ -1,14 +1,83 @@
+use morphing_core::FieldIndex;
+use morphing_core::Interpolate;
+use morphing_core::interpolate_step;
+
 use super::paint::Paint;
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct DashPattern {
     pub dashes: Vec<[f64; 2]>, // [dash_length, space_length]
     pub phase: f64,
 }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+// Both patterns are repeated up to the least common multiple of their lengths,
+// which leaves the rendered dashes unchanged, before blending.
+impl Interpolate for DashPattern {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        if self.dashes.is_empty() || other.dashes.is_empty() {
+            return interpolate_step(self, other, alpha);
+        }
+        let len = lcm(self.dashes.len(), other.dashes.len());
+        Self {
+            dashes: self
+                .dashes
+                .iter()
+                .cycle()
+                .zip(other.dashes.iter().cycle())
+                .take(len)
+                .map(|(dash, other_dash)| dash.interpolate(other_dash, alpha))
+                .collect(),
+            phase: self.phase.interpolate(&other.phase, alpha),
+        }
+    }
+}
+
+#[derive(
+    Clone, Debug, PartialEq, FieldIndex, Interpolate, serde::Deserialize, serde::Serialize,
+)]
 pub struct Stroke {
+    #[interpolate(with = "interpolate_dash_pattern")]
     pub dash_pattern: Option<DashPattern>,
+    #[interpolate(with = "interpolate_stroke_options")]
     pub options: lyon::tessellation::StrokeOptions,
     pub paint: Paint,
 }
+
+impl Stroke {
+    pub fn faded(&self, opacity: f32) -> Self {
+        Self {
+            paint: self.paint.faded(opacity),
+            ..self.clone()
+        }
+    }
+}
+
+fn interpolate_dash_pattern(
+    dash_pattern: &Option<DashPattern>,
+    other: &Option<DashPattern>,
+    alpha: f32,
+) -> Option<DashPattern> {
+    match (dash_pattern, other) {
+        (Some(dash_pattern), Some(other)) => Some(dash_pattern.interpolate(other, alpha)),
+        _ => interpolate_step(dash_pattern, other, alpha),
+    }
+}
+
+// Caps and joins are discrete and switch halfway.
+fn interpolate_stroke_options(
+    options: &lyon::tessellation::StrokeOptions,
+    other: &lyon::tessellation::StrokeOptions,
+    alpha: f32,
+) -> lyon::tessellation::StrokeOptions {
+    interpolate_step(options, other, alpha)
+        .with_line_width(options.line_width.interpolate(&other.line_width, alpha))
+        .with_miter_limit(options.miter_limit.interpolate(&other.miter_limit, alpha))
+        .with_tolerance(options.tolerance.interpolate(&other.tolerance, alpha))
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+    a / gcd(a, b) * b
+}
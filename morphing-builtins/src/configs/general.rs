@@ -0,0 +1,374 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use morphing_core::ConfigError;
+use morphing_core::ConfigLayers;
+use morphing_core::ConfigSchema;
+use morphing_core::Renderer;
+use morphing_core::RendererError;
+
+use crate::components::color::Color;
+
+// The `[general]` table, with fallbacks in `configs/general.toml`.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+pub struct GeneralConfig {
+    pub resolution: Resolution,
+    pub fps: Fps,
+    #[serde(with = "hex_color")]
+    #[schemars(schema_with = "hex_color::schema")]
+    pub foreground_color: Color,
+    #[serde(with = "hex_color")]
+    #[schemars(schema_with = "hex_color::schema")]
+    pub background_color: Color,
+}
+
+inventory::submit! {
+    ConfigSchema {
+        key: "general",
+        schema: |generator| generator.subschema_for::<GeneralConfig>(),
+    }
+}
+
+impl GeneralConfig {
+    pub fn from_layers(layers: &ConfigLayers) -> Result<Self, ConfigError> {
+        #[derive(serde::Deserialize)]
+        struct Table {
+            general: GeneralConfig,
+        }
+
+        layers.deserialize::<Table>().map(|table| table.general)
+    }
+
+    pub fn renderer(&self) -> Result<Renderer, RendererError> {
+        Renderer::new(
+            self.resolution.width,
+            self.resolution.height,
+            self.background_color.into(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+}
+
+#[derive(Debug)]
+pub enum ResolutionError {
+    Malformed(String),
+    Zero,
+}
+
+impl fmt::Display for ResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(s) => write!(
+                f,
+                "Expected `WxH`, `720p`, `1080p`, `1440p`, `4k` or `square-1080`, found `{s}`"
+            ),
+            Self::Zero => write!(f, "Resolution should not be zero"),
+        }
+    }
+}
+
+impl std::error::Error for ResolutionError {}
+
+impl FromStr for Resolution {
+    type Err = ResolutionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = match s.trim().to_ascii_lowercase().as_str() {
+            "720p" => (1280, 720),
+            "1080p" => (1920, 1080),
+            "1440p" => (2560, 1440),
+            "4k" | "2160p" => (3840, 2160),
+            "square-1080" => (1080, 1080),
+            size => size
+                .split_once('x')
+                .and_then(|(width, height)| {
+                    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+                })
+                .ok_or_else(|| ResolutionError::Malformed(s.into()))?,
+        };
+        if width == 0 || height == 0 {
+            return Err(ResolutionError::Zero);
+        }
+        Ok(Self { width, height })
+    }
+}
+
+impl TryFrom<String> for Resolution {
+    type Error = ResolutionError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl From<Resolution> for String {
+    fn from(resolution: Resolution) -> Self {
+        resolution.to_string()
+    }
+}
+
+impl schemars::JsonSchema for Resolution {
+    fn schema_name() -> Cow<'static, str> {
+        "Resolution".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A preset or `WxH` in pixels.",
+            "anyOf": [
+                { "enum": ["720p", "1080p", "1440p", "4k", "2160p", "square-1080"] },
+                { "type": "string", "pattern": "^\\s*[0-9]+\\s*[xX]\\s*[0-9]+\\s*$" },
+            ],
+        })
+    }
+}
+
+// Frames per second as a reduced fraction, so that NTSC rates like `30000/1001` stay exact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "FpsRepr", into = "String")]
+pub struct Fps {
+    numerator: u32,
+    denominator: u32,
+}
+
+impl Fps {
+    pub fn new(numerator: u32, denominator: u32) -> Result<Self, FpsError> {
+        if numerator == 0 || denominator == 0 {
+            return Err(FpsError::Zero);
+        }
+        let divisor = gcd(numerator, denominator);
+        Ok(Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        })
+    }
+
+    pub fn numerator(&self) -> u32 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> u32 {
+        self.denominator
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    // Number of whole frames covering `duration` seconds.
+    pub fn frame_count(&self, duration: f64) -> u64 {
+        (duration * self.as_f64()).ceil() as u64
+    }
+
+    // Start time of the `frame`-th frame, computed exactly before rounding.
+    pub fn frame_time(&self, frame: u64) -> f64 {
+        (frame * self.denominator as u64) as f64 / self.numerator as f64
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[derive(Debug)]
+pub enum FpsError {
+    Malformed(String),
+    Zero,
+}
+
+impl fmt::Display for FpsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(s) => write!(f, "Expected a number or `N/D`, found `{s}`"),
+            Self::Zero => write!(f, "Fps should be positive"),
+        }
+    }
+}
+
+impl std::error::Error for FpsError {}
+
+impl FromStr for Fps {
+    type Err = FpsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((numerator, denominator)) => Self::new(
+                numerator
+                    .trim()
+                    .parse()
+                    .map_err(|_| FpsError::Malformed(s.into()))?,
+                denominator
+                    .trim()
+                    .parse()
+                    .map_err(|_| FpsError::Malformed(s.into()))?,
+            ),
+            None => s
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| FpsError::Malformed(s.into()))
+                .and_then(Self::try_from),
+        }
+    }
+}
+
+// Decimal rates are kept to three decimal places, e.g. `29.97` becomes `2997/100`.
+impl TryFrom<f64> for Fps {
+    type Error = FpsError;
+
+    fn try_from(fps: f64) -> Result<Self, Self::Error> {
+        let numerator = (fps * 1000.0).round();
+        if !(1.0..=u32::MAX as f64).contains(&numerator) {
+            return Err(FpsError::Zero);
+        }
+        Self::new(numerator as u32, 1000)
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum FpsRepr {
+    Number(f64),
+    Text(String),
+}
+
+impl TryFrom<FpsRepr> for Fps {
+    type Error = FpsError;
+
+    fn try_from(repr: FpsRepr) -> Result<Self, Self::Error> {
+        match repr {
+            FpsRepr::Number(fps) => fps.try_into(),
+            FpsRepr::Text(s) => s.parse(),
+        }
+    }
+}
+
+impl fmt::Display for Fps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl From<Fps> for String {
+    fn from(fps: Fps) -> Self {
+        fps.to_string()
+    }
+}
+
+impl schemars::JsonSchema for Fps {
+    fn schema_name() -> Cow<'static, str> {
+        "Fps".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "Frames per second, either a number or a fraction `N/D`.",
+            "anyOf": [
+                { "type": "number", "exclusiveMinimum": 0 },
+                { "type": "string", "pattern": "^\\s*([0-9]+\\s*/\\s*[0-9]+|[0-9]*\\.?[0-9]+)\\s*$" },
+            ],
+        })
+    }
+}
+
+// Colors are written as hex strings in configs, parsed by `Color::from_str`.
+mod hex_color {
+    use crate::components::color::Color;
+
+    pub(super) fn serialize<S>(color: &Color, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let (r, g, b, a) = color.into_format::<u8, u8>().into_components();
+        serializer.serialize_str(&if a == u8::MAX {
+            format!("#{r:02X}{g:02X}{b:02X}")
+        } else {
+            format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+        })
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Color, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|error| serde::de::Error::custom(format!("Invalid hex color `{s}`: {error}")))
+    }
+
+    // `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`, with the `#` being optional.
+    pub(super) fn schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": "^#?([0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$",
+        })
+    }
+}
+
+impl From<Color> for wgpu::Color {
+    fn from(color: Color) -> Self {
+        let (r, g, b, a) = color.into_linear::<f32, f32>().into_components();
+        wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: a as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod general_tests {
+    use super::*;
+
+    #[test]
+    fn test_resolution_and_fps() {
+        assert_eq!(
+            "1280x720".parse::<Resolution>().unwrap(),
+            "720p".parse().unwrap()
+        );
+        assert_eq!(
+            "square-1080".parse::<Resolution>().unwrap().to_string(),
+            "1080x1080"
+        );
+        assert!("1920*1080".parse::<Resolution>().is_err());
+        assert!("0x1080".parse::<Resolution>().is_err());
+
+        let ntsc: Fps = "30000/1001".parse().unwrap();
+        assert_eq!((ntsc.numerator(), ntsc.denominator()), (30000, 1001));
+        assert_eq!(ntsc.frame_time(30000), 1001.0);
+        assert_eq!("60".parse::<Fps>().unwrap(), Fps::try_from(60.0).unwrap());
+        assert_eq!(Fps::try_from(29.97).unwrap().to_string(), "2997/100");
+        assert!("0/1".parse::<Fps>().is_err());
+    }
+
+    #[test]
+    fn test_config_schema() {
+        let chapter = morphing_core::__link::chapter_symbol("builtins", Vec::new(), false);
+        let properties = chapter.schema().get("properties").unwrap();
+        assert!(properties["general"].is_object());
+        assert!(properties["typst"].is_object());
+    }
+}
@@ -1,6 +1,12 @@
-use std::borrow::Cow;
 use std::sync::OnceLock;
 
+use encase::ShaderType;
+use morphing_core::ComponentShaderTypes;
+use morphing_core::Layer;
+use morphing_core::TEXTURE_FORMAT;
+use morphing_core::include_wgsl;
+use morphing_core::layer;
+
 use super::super::components::camera_transform::CameraTransform2DShaderTypes;
 use super::super::components::paint::PaintShaderTypes;
 use super::super::components::transform::TransformShaderTypes;
@@ -13,225 +19,47 @@ struct Vertex {
 }
 */
 #[derive(encase::ShaderType)]
-pub(crate) struct Vertex {
-    position: nalgebra::Vector2<f32>,
-}
-
-#[allow(non_camel_case_types)]
-pub struct BuiltinPlanarLayer<
-    camera_transform_2d = ::morphing_core::stage::ChannelType<CameraTransform2DPresentation>,
-    planar_trimesh = ::morphing_core::stage::ChannelType<PlanarTrimeshPresentation>,
-> {
-    pub camera_transform_2d: camera_transform_2d,
-    pub planar_trimesh: planar_trimesh,
-}
-
-impl ::morphing_core::stage::Archive for BuiltinPlanarLayer {
-    type Output = BuiltinPlanarLayer<
-        <::morphing_core::stage::ChannelType<CameraTransform2DPresentation> as ::morphing_core::stage::Archive>::Output,
-        <::morphing_core::stage::ChannelType<PlanarTrimeshPresentation> as ::morphing_core::stage::Archive>::Output,
-    >;
-
-    fn new() -> Self {
-        BuiltinPlanarLayer {
-            camera_transform_2d: ::morphing_core::stage::Archive::new(),
-            planar_trimesh: ::morphing_core::stage::Archive::new(),
-        }
-    }
-
-    fn merge<TE>(
-        &self,
-        output: Self::Output,
-        alive_id: usize,
-        time_eval: &TE,
-        parent_time_interval: ::core::range::Range<::morphing_core::timer::Time>,
-        child_time_interval: ::core::range::Range<::morphing_core::timer::Time>,
-    ) where
-        TE: ::morphing_core::timer::IncreasingTimeEval<
-            OutputTimeMetric = ::morphing_core::timer::NormalizedTimeMetric,
-        >,
-    {
-        self.camera_transform_2d.merge(
-            output.camera_transform_2d,
-            alive_id,
-            time_eval,
-            parent_time_interval,
-            child_time_interval,
-        );
-        self.planar_trimesh.merge(
-            output.planar_trimesh,
-            alive_id,
-            time_eval,
-            parent_time_interval,
-            child_time_interval,
-        );
-    }
-
-    fn archive(self) -> Self::Output {
-        BuiltinPlanarLayer {
-            camera_transform_2d: self.camera_transform_2d.archive(),
-            planar_trimesh: self.planar_trimesh.archive(),
-        }
-    }
+pub struct Vertex {
+    pub position: nalgebra::Vector2<f32>,
 }
 
-impl ::morphing_core::stage::Layer for BuiltinPlanarLayer {
-    type Residue<'t, W, LI> = BuiltinPlanarLayer<
-        ::morphing_core::stage::ChannelAttachment<'t, W, LI, Self, BuiltinPlanarLayerCameraTransfrom2DChannel, ::morphing_core::stage::ChannelType<CameraTransform2DPresentation>, CameraTransform2DPresentation>,
-        ::morphing_core::stage::ChannelAttachment<'t, W, LI, Self, BuiltinPlanarLayerPlanarTrimeshChannel, ::morphing_core::stage::ChannelType<PlanarTrimeshPresentation>, PlanarTrimeshPresentation>,
-    > where
-        W: ::morphing_core::stage::World,
-        LI: ::morphing_core::stage::LayerIndex<W, Layer = Self>;
-
-    fn attachment<'t, W, LI>(
-        &'t self,
-        config: &'t ::morphing_core::config::Config,
-        timer: &'t ::morphing_core::timer::Timer,
-        world: &'t W,
-    ) -> ::morphing_core::stage::LayerAttachment<'t, W, LI, Self, Self::Residue<'t, W, LI>>
-    where
-        W: ::morphing_core::stage::World,
-        LI: ::morphing_core::stage::LayerIndex<W, Layer = Self>,
-    {
-        ::morphing_core::stage::LayerAttachment {
-            config,
-            timer,
-            world,
-            layer_index: ::std::marker::PhantomData,
-            layer: self,
-            residue: BuiltinPlanarLayer {
-                camera_transform_2d: self
-                    .camera_transform_2d
-                    .attachment(config, timer, world, self),
-                planar_trimesh: self.planar_trimesh.attachment(config, timer, world, self),
-            },
-        }
-    }
+#[layer]
+pub struct BuiltinPlanarLayer {
+    pub camera_transform_2d: CameraTransform2DPresentation,
+    pub planar_trimesh: PlanarTrimeshPresentation,
 }
 
-impl ::morphing_core::stage::Allocate
-    for BuiltinPlanarLayer<
-        <::morphing_core::stage::ChannelType<CameraTransform2DPresentation> as ::morphing_core::stage::Archive>::Output,
-        <::morphing_core::stage::ChannelType<PlanarTrimeshPresentation> as ::morphing_core::stage::Archive>::Output,
-    >
-{
-    type Output = BuiltinPlanarLayer<
-        <<::morphing_core::stage::ChannelType<CameraTransform2DPresentation> as ::morphing_core::stage::Archive>::Output as ::morphing_core::stage::Allocate>::Output,
-        <<::morphing_core::stage::ChannelType<PlanarTrimeshPresentation> as ::morphing_core::stage::Archive>::Output as ::morphing_core::stage::Allocate>::Output,
-    >;
-
-    fn allocate(self, slot_key_generator_type_map: &mut ::morphing_core::storable::SlotKeyGeneratorTypeMap) -> Self::Output {
-        BuiltinPlanarLayer {
-            camera_transform_2d: self.camera_transform_2d.allocate(slot_key_generator_type_map),
-            planar_trimesh: self.planar_trimesh.allocate(slot_key_generator_type_map),
+// Every trimesh is drawn once through every camera.
+impl Layer for BuiltinPlanarLayer {
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        for camera_transform_2d in &self.camera_transform_2d {
+            render_pass.set_bind_group(2, &camera_transform_2d.camera_transform_2d_bind_group, &[]);
+            for planar_trimesh in &self.planar_trimesh {
+                render_pass.set_pipeline(planar_trimesh.pipeline);
+                render_pass.set_bind_group(0, &planar_trimesh.transform_bind_group, &[]);
+                render_pass.set_bind_group(1, &planar_trimesh.paint_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, planar_trimesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(
+                    planar_trimesh.index_buffer.slice(..),
+                    wgpu::IndexFormat::Uint32,
+                );
+                render_pass.draw_indexed(0..planar_trimesh.index_count, 0, 0..1);
+            }
         }
     }
 }
 
-impl ::morphing_core::stage::Prepare
-    for BuiltinPlanarLayer<
-        <<::morphing_core::stage::ChannelType<CameraTransform2DPresentation> as ::morphing_core::stage::Archive>::Output as ::morphing_core::stage::Allocate>::Output,
-        <<::morphing_core::stage::ChannelType<PlanarTrimeshPresentation> as ::morphing_core::stage::Archive>::Output as ::morphing_core::stage::Allocate>::Output,
-    >
-{
-    type Output = BuiltinPlanarLayer<
-        <<<::morphing_core::stage::ChannelType<CameraTransform2DPresentation> as ::morphing_core::stage::Archive>::Output as ::morphing_core::stage::Allocate>::Output as ::morphing_core::stage::Prepare>::Output,
-        <<<::morphing_core::stage::ChannelType<PlanarTrimeshPresentation> as ::morphing_core::stage::Archive>::Output as ::morphing_core::stage::Allocate>::Output as ::morphing_core::stage::Prepare>::Output,
-    >;
-
-    fn prepare(
-        &self,
-        time: ::morphing_core::timer::Time,
-        storage_type_map: &mut ::morphing_core::storable::StorageTypeMap,
-        device: &::wgpu::Device,
-        queue: &::wgpu::Queue,
-        format: ::wgpu::TextureFormat,
-    ) -> Self::Output {
-        BuiltinPlanarLayer {
-            camera_transform_2d: self
-                .camera_transform_2d
-                .prepare(time, storage_type_map, device, queue, format),
-            planar_trimesh: self
-                .planar_trimesh
-                .prepare(time, storage_type_map, device, queue, format),
-        }
-    }
-}
-
-pub struct BuiltinPlanarLayerCameraTransfrom2DChannel;
-
-impl ::morphing_core::stage::ChannelIndex<BuiltinPlanarLayer>
-    for BuiltinPlanarLayerCameraTransfrom2DChannel
-{
-    type Channel = ::morphing_core::stage::ChannelType<CameraTransform2DPresentation>;
-
-    fn index_attachment<'t, 'a, W, LI>(
-        attachment: &'a ::morphing_core::stage::LayerAttachment<
-            't,
-            W,
-            LI,
-            BuiltinPlanarLayer,
-            <BuiltinPlanarLayer as ::morphing_core::stage::Layer>::Residue<'t, W, LI>,
-        >,
-    ) -> &'a ::morphing_core::stage::ChannelAttachment<
-        't,
-        W,
-        LI,
-        BuiltinPlanarLayer,
-        Self,
-        Self::Channel,
-        <Self::Channel as ::morphing_core::stage::Channel>::Presentation,
-    >
-    where
-        W: ::morphing_core::stage::World,
-        LI: ::morphing_core::stage::LayerIndex<W, Layer = BuiltinPlanarLayer>,
-    {
-        &attachment.residue.camera_transform_2d
-    }
-}
-
-pub struct BuiltinPlanarLayerPlanarTrimeshChannel;
-
-impl ::morphing_core::stage::ChannelIndex<BuiltinPlanarLayer>
-    for BuiltinPlanarLayerPlanarTrimeshChannel
-{
-    type Channel = ::morphing_core::stage::ChannelType<PlanarTrimeshPresentation>;
-
-    fn index_attachment<'t, 'a, W, LI>(
-        attachment: &'a ::morphing_core::stage::LayerAttachment<
-            't,
-            W,
-            LI,
-            BuiltinPlanarLayer,
-            <BuiltinPlanarLayer as ::morphing_core::stage::Layer>::Residue<'t, W, LI>,
-        >,
-    ) -> &'a ::morphing_core::stage::ChannelAttachment<
-        't,
-        W,
-        LI,
-        BuiltinPlanarLayer,
-        Self,
-        Self::Channel,
-        <Self::Channel as ::morphing_core::stage::Channel>::Presentation,
-    >
-    where
-        W: ::morphing_core::stage::World,
-        LI: ::morphing_core::stage::LayerIndex<W, Layer = BuiltinPlanarLayer>,
-    {
-        &attachment.residue.planar_trimesh
-    }
-}
-
-// hand-written
-
 static BUILTIN_PLANAR_PIPELINE: OnceLock<wgpu::RenderPipeline> = OnceLock::new();
-fn builtin_planar_pipeline(device: &wgpu::Device) -> &'static wgpu::RenderPipeline {
+pub(crate) fn builtin_planar_pipeline(device: &wgpu::Device) -> &'static wgpu::RenderPipeline {
     BUILTIN_PLANAR_PIPELINE.get_or_init(|| {
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
-                "../shaders/builtin_planar.wgsl"
-            ))),
+            source: wgpu::ShaderSource::Wgsl(include_wgsl!(
+                "src/shaders/builtin_planar.wgsl",
+                TransformShaderTypes,
+                PaintShaderTypes,
+                CameraTransform2DShaderTypes,
+            )),
         });
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
@@ -247,7 +75,8 @@ fn builtin_planar_pipeline(device: &wgpu::Device) -> &'static wgpu::RenderPipeli
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader_module,
-                entry_point: "vs_main",
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
                 buffers: &[wgpu::VertexBufferLayout {
                     array_stride: Vertex::min_size().get(),
                     step_mode: wgpu::VertexStepMode::Vertex,
@@ -260,9 +89,10 @@ fn builtin_planar_pipeline(device: &wgpu::Device) -> &'static wgpu::RenderPipeli
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader_module,
-                entry_point: "fs_main",
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8UnormSrgb, // TODO: check if color channels messed up?
+                    format: TEXTURE_FORMAT,
                     blend: Some(wgpu::BlendState {
                         color: wgpu::BlendComponent {
                             src_factor: wgpu::BlendFactor::One,
@@ -282,7 +112,8 @@ fn builtin_planar_pipeline(device: &wgpu::Device) -> &'static wgpu::RenderPipeli
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                // Tessellated triangles come in either winding, which the CPU rasterizer also accepts.
+                cull_mode: None,
                 // Setting this to anything other than Fill requires Features::POLYGON_MODE_LINE
                 // or Features::POLYGON_MODE_POINT
                 polygon_mode: wgpu::PolygonMode::Fill,
@@ -298,21 +129,102 @@ fn builtin_planar_pipeline(device: &wgpu::Device) -> &'static wgpu::RenderPipeli
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         })
     })
 }
 
-impl ::morphing_core::stage::Render
-    for BuiltinPlanarLayer<
-        Vec<::morphing_core::timeline::PresentationKey<CameraTransform2DPresentation>>,
-        Vec<::morphing_core::timeline::PresentationKey<PlanarTrimeshPresentation>>,
-    >
-{
-    fn render(
-        &self,
-        storage_type_map: &::morphing_core::storable::StorageTypeMap,
-        encoder: &mut ::wgpu::CommandEncoder,
-        target: &::wgpu::TextureView,
-    ) {
+#[cfg(test)]
+mod builtin_planar_tests {
+    use morphing_core::ComponentShaderTypes;
+    use morphing_core::WgslBinding;
+
+    use super::super::super::components::camera_transform::CameraTransform2DShaderTypes;
+    use super::super::super::components::camera_transform::CameraTransform3DShaderTypes;
+    use super::super::super::components::paint::PaintShaderTypes;
+    use super::super::super::components::transform::TransformShaderTypes;
+
+    fn parse_module(source: &str) -> naga::Module {
+        let module = naga::front::wgsl::parse_str(source)
+            .unwrap_or_else(|error| panic!("{}", error.emit_to_string(source)));
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .unwrap_or_else(|error| panic!("{}", error.emit_to_string(source)));
+        module
+    }
+
+    // Member names and offsets of the named struct, with its size.
+    fn struct_layout(module: &naga::Module, name: &str) -> Option<(Vec<(String, u32)>, u32)> {
+        module.types.iter().find_map(|(_, ty)| match &ty.inner {
+            naga::TypeInner::Struct { members, span } if ty.name.as_deref() == Some(name) => {
+                Some((
+                    members
+                        .iter()
+                        .map(|member| (member.name.clone().unwrap_or_default(), member.offset))
+                        .collect(),
+                    *span,
+                ))
+            }
+            _ => None,
+        })
+    }
+
+    // Checks the generated declarations against the Rust layouts, and the structs they declare
+    // against the hand-written copies in the shader.
+    fn check_declarations<T: ComponentShaderTypes>(group: u32, shader: &naga::Module) {
+        let module = parse_module(&T::wgsl_declarations(group));
+        let mut bindings_len = 0;
+        for (_, global_variable) in module.global_variables.iter() {
+            let resource_binding = global_variable.binding.as_ref().unwrap();
+            assert_eq!(resource_binding.group, group);
+            let binding = WgslBinding::find(T::BINDINGS, resource_binding.binding).unwrap();
+            assert_eq!(global_variable.name.as_deref(), Some(binding.name));
+            assert_eq!(
+                u64::from(module.types[global_variable.ty].inner.size(module.to_ctx())),
+                binding.size,
+            );
+            let member_struct_ty = match module.types[global_variable.ty].inner {
+                naga::TypeInner::Array { base, .. } => base,
+                _ => global_variable.ty,
+            };
+            let naga::TypeInner::Struct { members, .. } = &module.types[member_struct_ty].inner
+            else {
+                panic!("`{}` is not bound to a struct", binding.name);
+            };
+            assert_eq!(
+                members
+                    .iter()
+                    .map(|member| (member.name.as_deref().unwrap(), u64::from(member.offset)))
+                    .collect::<Vec<_>>(),
+                binding
+                    .members
+                    .iter()
+                    .map(|member| (member.name, member.offset))
+                    .collect::<Vec<_>>(),
+            );
+            bindings_len += 1;
+        }
+        assert_eq!(bindings_len, T::BINDINGS.len());
+
+        for (_, ty) in module.types.iter() {
+            if let (naga::TypeInner::Struct { .. }, Some(name)) = (&ty.inner, &ty.name) {
+                let layout = struct_layout(&module, name);
+                if let Some(shader_layout) = struct_layout(shader, name) {
+                    assert_eq!(layout, Some(shader_layout), "struct `{name}` differs");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wgsl_declarations() {
+        let shader = parse_module(include_str!("../shaders/builtin_planar.wgsl"));
+        check_declarations::<TransformShaderTypes>(0, &shader);
+        check_declarations::<PaintShaderTypes>(1, &shader);
+        check_declarations::<CameraTransform2DShaderTypes>(2, &shader);
+        check_declarations::<CameraTransform3DShaderTypes>(0, &shader);
     }
 }
@@ -0,0 +1,339 @@
+use std::f32::consts::PI;
+
+use geometric_algebra::Transformation;
+
+use super::super::components::camera_transform::CameraTransform2D;
+use super::super::components::color::Color;
+use super::super::components::motor::Motor2D;
+use super::super::components::motor::Motor3D;
+use super::super::components::paint::Gradient;
+use super::super::components::paint::Paint;
+use super::super::components::transform::Transform;
+use super::builtin_planar::Vertex;
+
+// CPU counterpart of the builtin planar pipeline, for environments without a GPU.
+// The fragment stage is a port of `builtin_planar.wgsl`, and the pipeline state follows
+// `builtin_planar.rs`: additive blending, linear color accumulation and sRGB encoding on output.
+// Unlike the GPU pipeline, triangles of both windings are rasterized.
+pub struct BuiltinPlanarCanvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<nalgebra::Vector4<f32>>,
+}
+
+impl BuiltinPlanarCanvas {
+    pub fn new(width: u32, height: u32, background_color: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background_color.into(); (width * height) as usize],
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn draw(
+        &mut self,
+        vertex_buffers: &lyon::tessellation::VertexBuffers<Vertex, u32>,
+        paint: &Paint,
+        transform: &Transform,
+        camera_transform_2d: &CameraTransform2D,
+    ) {
+        let screen_positions: Vec<_> = vertex_buffers
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let clip_position = apply_projection_matrix(
+                    &camera_transform_2d.projection_matrix,
+                    apply_motor_2d(
+                        &camera_transform_2d.view_motor,
+                        apply_transform(transform, vertex.position),
+                    ),
+                );
+                // Viewport transform, with pixel centers at half-integers.
+                nalgebra::Vector2::new(
+                    (clip_position.x + 1.0) / 2.0 * self.width as f32,
+                    (1.0 - clip_position.y) / 2.0 * self.height as f32,
+                )
+            })
+            .collect();
+        for triangle in vertex_buffers.indices.chunks_exact(3) {
+            let [i0, i1, i2] = [0, 1, 2].map(|i| triangle[i] as usize);
+            self.rasterize_triangle(
+                [
+                    screen_positions[i0],
+                    screen_positions[i1],
+                    screen_positions[i2],
+                ],
+                [
+                    vertex_buffers.vertices[i0].position,
+                    vertex_buffers.vertices[i1].position,
+                    vertex_buffers.vertices[i2].position,
+                ],
+                paint,
+            );
+        }
+    }
+
+    // Tightly packed RGBA8 pixels in row-major order, matching `Renderer::render`.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| {
+                let color = color.map(|component| component.clamp(0.0, 1.0));
+                [
+                    encode_srgb(color.x),
+                    encode_srgb(color.y),
+                    encode_srgb(color.z),
+                    (color.w * 255.0).round() as u8,
+                ]
+            })
+            .collect()
+    }
+
+    fn rasterize_triangle(
+        &mut self,
+        screen_positions: [nalgebra::Vector2<f32>; 3],
+        positions: [nalgebra::Vector2<f32>; 3],
+        paint: &Paint,
+    ) {
+        let [mut p0, p1, mut p2] = screen_positions;
+        let [mut q0, q1, mut q2] = positions;
+        let mut area = edge_function(p0, p1, p2);
+        if area == 0.0 {
+            return;
+        }
+        // Orient counterclockwise in screen space (y pointing downwards).
+        if area < 0.0 {
+            std::mem::swap(&mut p0, &mut p2);
+            std::mem::swap(&mut q0, &mut q2);
+            area = -area;
+        }
+
+        let x_min = p0.x.min(p1.x).min(p2.x).floor().max(0.0) as u32;
+        let x_max = (p0.x.max(p1.x).max(p2.x).ceil() as u32).min(self.width);
+        let y_min = p0.y.min(p1.y).min(p2.y).floor().max(0.0) as u32;
+        let y_max = (p0.y.max(p1.y).max(p2.y).ceil() as u32).min(self.height);
+        let edges = [(p1, p2), (p2, p0), (p0, p1)];
+        for y in y_min..y_max {
+            for x in x_min..x_max {
+                let pixel_center = nalgebra::Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let weights = edges.map(|(from, to)| edge_function(from, to, pixel_center));
+                if !weights
+                    .iter()
+                    .zip(edges)
+                    .all(|(&weight, (from, to))| covers(weight, from, to))
+                {
+                    continue;
+                }
+                let position = (q0 * weights[0] + q1 * weights[1] + q2 * weights[2]) / area;
+                let index = (y * self.width + x) as usize;
+                self.pixels[index] += fs_main(paint, position);
+            }
+        }
+    }
+}
+
+fn edge_function(
+    from: nalgebra::Vector2<f32>,
+    to: nalgebra::Vector2<f32>,
+    point: nalgebra::Vector2<f32>,
+) -> f32 {
+    (to.x - from.x) * (point.y - from.y) - (to.y - from.y) * (point.x - from.x)
+}
+
+// Top-left rule, so that pixels on shared edges are only blended once.
+fn covers(weight: f32, from: nalgebra::Vector2<f32>, to: nalgebra::Vector2<f32>) -> bool {
+    let offset = to - from;
+    weight > 0.0 || (weight == 0.0 && ((offset.y == 0.0 && offset.x < 0.0) || offset.y > 0.0))
+}
+
+fn encode_srgb(component: f32) -> u8 {
+    let encoded = if component <= 0.0031308 {
+        component * 12.92
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+fn apply_transform(
+    transform: &Transform,
+    position: nalgebra::Vector2<f32>,
+) -> nalgebra::Vector2<f32> {
+    apply_motor_3d(
+        &transform.motor,
+        nalgebra::Vector3::new(position.x, position.y, 0.0) * transform.scale,
+    )
+    .xy()
+}
+
+fn apply_projection_matrix(
+    projection_matrix: &nalgebra::Matrix3<f32>,
+    position: nalgebra::Vector2<f32>,
+) -> nalgebra::Vector2<f32> {
+    let homogeneous_position =
+        projection_matrix * nalgebra::Vector3::new(position.x, position.y, 1.0);
+    homogeneous_position.xy() / homogeneous_position.z
+}
+
+// Points are embedded as `e12 + x e20 + y e01` and transformed by the sandwich product.
+fn apply_motor_2d(
+    Motor2D(motor): &Motor2D,
+    position: nalgebra::Vector2<f32>,
+) -> nalgebra::Vector2<f32> {
+    let point = motor.transformation(geometric_algebra::ppga2d::Point::new(
+        1.0,
+        position.y,
+        -position.x,
+    ));
+    nalgebra::Vector2::new(-point[2], point[1]) / point[0]
+}
+
+// Points are embedded as `e123 + x e032 + y e013 + z e021` and transformed by the sandwich product.
+fn apply_motor_3d(
+    Motor3D(motor): &Motor3D,
+    position: nalgebra::Vector3<f32>,
+) -> nalgebra::Vector3<f32> {
+    let point = motor.transformation(geometric_algebra::ppga3d::Point::new(
+        1.0, position.x, position.y, position.z,
+    ));
+    nalgebra::Vector3::new(point[1], point[2], point[3]) / point[0]
+}
+
+fn fs_main(paint: &Paint, position: nalgebra::Vector2<f32>) -> nalgebra::Vector4<f32> {
+    paint.gradients.iter().fold(
+        paint.color.into(),
+        |color: nalgebra::Vector4<f32>, gradient| {
+            color.component_mul(&eval_gradient_color(gradient, position))
+        },
+    )
+}
+
+fn eval_gradient_color(
+    gradient: &Gradient,
+    position: nalgebra::Vector2<f32>,
+) -> nalgebra::Vector4<f32> {
+    let from_position = gradient.from_position - position;
+    let to_position = gradient.to_position - position;
+    let p = gradient.radius_slope;
+    let q = gradient.radius_quotient;
+
+    let mid = q * to_position - from_position;
+    let offset = to_position - from_position;
+    let mid_dot_mid = mid.dot(&mid);
+    let mid_dot_offset = mid.dot(&offset);
+    let offset_dot_offset = offset.dot(&offset);
+    let from_cross_to = from_position.x * to_position.y - from_position.y * to_position.x;
+    let eta = mid.dot(&from_position) / mid_dot_offset;
+    let kappa = (from_cross_to * from_cross_to) / (mid_dot_mid * offset_dot_offset);
+    let sigma = (1.0 - q) * (p - 2.0) / (1.0 - (p - 1.0) * (1.0 - q) * (1.0 - q) * kappa);
+    let nu = sigma * kappa / (1.0 + (1.0 - sigma * sigma * kappa).sqrt());
+    let alpha = eta * (1.0 + (1.0 - q) * nu) + q * nu;
+    let theta = mid_dot_offset.atan2((1.0 - q) * from_cross_to);
+
+    interpolate_color(&gradient.radial_stops, alpha - alpha.floor()).component_mul(
+        &interpolate_color(&gradient.angular_stops, (1.0 + theta / PI) / 2.0),
+    )
+}
+
+// Same binary search as `interpolate_radial_color` and `interpolate_angular_color`.
+fn interpolate_color(stops: &[(f32, Color)], alpha: f32) -> nalgebra::Vector4<f32> {
+    let (Some(&(first_alpha, first_color)), Some(&(last_alpha, last_color))) =
+        (stops.first(), stops.last())
+    else {
+        return nalgebra::Vector4::repeat(1.0);
+    };
+    if alpha < first_alpha {
+        return first_color.into();
+    }
+    if alpha >= last_alpha {
+        return last_color.into();
+    }
+    let mut start = 0;
+    let mut end = stops.len() - 1;
+    while start + 1 < end {
+        let mid = start + (end - start) / 2;
+        if alpha < stops[mid].0 {
+            end = mid;
+        } else {
+            start = mid;
+        }
+    }
+    let (start_alpha, start_color) = stops[start];
+    let (end_alpha, end_color) = stops[end];
+    let start_color: nalgebra::Vector4<f32> = start_color.into();
+    let end_color: nalgebra::Vector4<f32> = end_color.into();
+    start_color + (end_color - start_color) * ((alpha - start_alpha) / (end_alpha - start_alpha))
+}
+
+#[cfg(test)]
+mod builtin_planar_cpu_tests {
+    use super::super::super::components::camera_transform::CameraTransform2D;
+    use super::super::super::components::color::Color;
+    use super::super::super::components::motor::Motor2D;
+    use super::super::super::components::paint::Paint;
+    use super::super::super::components::transform::Transform;
+    use super::super::builtin_planar::Vertex;
+    use super::BuiltinPlanarCanvas;
+    use super::interpolate_color;
+
+    fn identity_camera_transform_2d() -> CameraTransform2D {
+        CameraTransform2D {
+            view_motor: Motor2D::from(nalgebra::Vector4::new(1.0, 0.0, 0.0, 0.0)),
+            projection_matrix: nalgebra::Matrix3::identity(),
+        }
+    }
+
+    #[test]
+    fn test_full_screen_quad() {
+        let vertex_buffers = lyon::tessellation::VertexBuffers {
+            vertices: [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .map(|(x, y)| Vertex {
+                    position: nalgebra::Vector2::new(x, y),
+                })
+                .into(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+        };
+        let mut canvas = BuiltinPlanarCanvas::new(4, 4, Color::min());
+        canvas.draw(
+            &vertex_buffers,
+            &Paint {
+                color: Color::max(),
+                gradients: Vec::new(),
+            },
+            &Transform::default(),
+            &identity_camera_transform_2d(),
+        );
+        // Every pixel is covered exactly once, even along the shared diagonal.
+        assert!(canvas.to_rgba8().iter().all(|&component| component == 255));
+    }
+
+    #[test]
+    fn test_interpolate_color() {
+        let stops = [
+            (0.0, Color::from(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))),
+            (0.5, Color::from(palette::Srgba::new(1.0, 0.0, 0.0, 1.0))),
+            (1.0, Color::from(palette::Srgba::new(1.0, 1.0, 0.0, 1.0))),
+        ];
+        assert_eq!(interpolate_color(&[], 0.3), nalgebra::Vector4::repeat(1.0));
+        assert_eq!(
+            interpolate_color(&stops, -1.0),
+            nalgebra::Vector4::new(0.0, 0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            interpolate_color(&stops, 0.25),
+            nalgebra::Vector4::new(0.5, 0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            interpolate_color(&stops, 0.75),
+            nalgebra::Vector4::new(1.0, 0.5, 0.0, 1.0)
+        );
+        assert_eq!(
+            interpolate_color(&stops, 2.0),
+            nalgebra::Vector4::new(1.0, 1.0, 0.0, 1.0)
+        );
+    }
+}
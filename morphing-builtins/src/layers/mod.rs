@@ -1 +1,2 @@
 pub mod builtin_planar;
+pub mod builtin_planar_cpu;
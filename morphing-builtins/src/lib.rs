@@ -1,16 +1,18 @@
-// #![feature(new_range_api)]
+// Lets derive macros refer to the core crate as `::morphing`.
+extern crate morphing_core as morphing;
 
-// use morphing_core::config::ConfigFallbackContent;
+use morphing_core::ConfigFallbackContent;
 
-// pub mod components;
-// pub mod layers;
-// pub mod mobjects;
-// pub mod presentations;
-// pub mod timelines;
+pub mod components;
+pub mod configs;
+pub mod layers;
+pub mod mobjects;
+pub mod presentations;
+pub mod timelines;
 
-// inventory::submit! {
-//     ConfigFallbackContent(include_str!("configs/general.toml"))
-// }
-// inventory::submit! {
-//     ConfigFallbackContent(include_str!("configs/typst.toml"))
-// }
+inventory::submit! {
+    ConfigFallbackContent(include_str!("configs/general.toml"))
+}
+inventory::submit! {
+    ConfigFallbackContent(include_str!("configs/typst.toml"))
+}
@@ -1,35 +1,60 @@
-use std::borrow::Cow;
-use std::sync::OnceLock;
-
-use encase::ShaderType;
 use lyon::tessellation::{
-    BuffersBuilder, FillOptions, FillVertex, FillVertexConstructor, StrokeOptions, StrokeVertex,
-    StrokeVertexConstructor,
+    BuffersBuilder, FillOptions, FillVertexConstructor, StrokeOptions, StrokeVertexConstructor,
 };
-use morphing_core::config::Config;
-use morphing_core::traits::Mobject;
-use morphing_core::traits::MobjectBuilder;
-use wgpu::util::DeviceExt;
+use morphing_core::FieldIndex;
+use morphing_core::Interpolate;
+use morphing_core::Layer;
+use morphing_core::Lifecycle;
+use morphing_core::MobjectBuilder;
+use morphing_core::Time;
 
-use super::super::components::camera::{Camera, CameraShaderTypes};
 use super::super::components::color::Palette;
-use super::super::components::component::Component;
-use super::super::components::component::ComponentShaderTypes;
 use super::super::components::fill::Fill;
-use super::super::components::paint::{Paint, PaintShaderTypes};
+use super::super::components::paint::Paint;
 use super::super::components::path::Path;
 use super::super::components::stroke::Stroke;
 use super::super::components::transform::Transform;
-use super::super::components::transform::TransformShaderTypes;
-
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+use super::super::layers::builtin_planar::BuiltinPlanarLayer;
+use super::super::layers::builtin_planar::Vertex;
+use super::super::presentations::camera_transform::CameraTransform2DPresentation;
+use super::super::presentations::planar_trimesh::PlanarTrimeshPresentation;
+use super::camera::PlanarCamera;
+
+#[derive(
+    Clone, Debug, PartialEq, FieldIndex, Interpolate, serde::Deserialize, serde::Serialize,
+)]
 pub struct ShapeMobject {
     pub(crate) transform: Transform,
     pub(crate) path: Path,
+    #[interpolate(with = "interpolate_fill")]
     pub(crate) fill: Option<Fill>,
+    #[interpolate(with = "interpolate_stroke")]
     pub(crate) stroke: Option<Stroke>,
 }
 
+// A fill or stroke present on one side only fades in or out.
+fn interpolate_fill(fill: &Option<Fill>, other: &Option<Fill>, alpha: f32) -> Option<Fill> {
+    match (fill, other) {
+        (Some(fill), Some(other)) => Some(fill.interpolate(other, alpha)),
+        (Some(fill), None) => Some(fill.faded(1.0 - alpha)),
+        (None, Some(other)) => Some(other.faded(alpha)),
+        (None, None) => None,
+    }
+}
+
+fn interpolate_stroke(
+    stroke: &Option<Stroke>,
+    other: &Option<Stroke>,
+    alpha: f32,
+) -> Option<Stroke> {
+    match (stroke, other) {
+        (Some(stroke), Some(other)) => Some(stroke.interpolate(other, alpha)),
+        (Some(stroke), None) => Some(stroke.faded(1.0 - alpha)),
+        (None, Some(other)) => Some(other.faded(alpha)),
+        (None, None) => None,
+    }
+}
+
 struct VertexConstructor;
 
 impl FillVertexConstructor<Vertex> for VertexConstructor {
@@ -50,140 +75,54 @@ impl StrokeVertexConstructor<Vertex> for VertexConstructor {
     }
 }
 
-static PLANAR_TRNANGLES_PIPELINE: OnceLock<wgpu::RenderPipeline> = OnceLock::new();
-
-pub struct PlanarTrianglesPresentation {
-    pipeline: &'static wgpu::RenderPipeline,
-    transform_bind_group: wgpu::BindGroup,
-    paint_bind_group: wgpu::BindGroup,
-    camera_bind_group: wgpu::BindGroup,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
+// The layer along with the shape it is built from, so that it is rebuilt only once the shape
+// changes, e.g. while animated.
+pub struct ShapePresentation {
+    shape: ShapeMobject,
+    layer: BuiltinPlanarLayer,
 }
 
-impl PlanarTrianglesPresentation {
-    fn pipeline(device: &wgpu::Device) -> &'static wgpu::RenderPipeline {
-        PLANAR_TRNANGLES_PIPELINE.get_or_init(|| {
-            let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
-                    "../shaders/planar_triangles.wgsl"
-                ))),
-            });
-            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: None,
-                bind_group_layouts: &[
-                    TransformShaderTypes::bind_group_layout(device),
-                    PaintShaderTypes::bind_group_layout(device),
-                    CameraShaderTypes::bind_group_layout(device),
-                ],
-                push_constant_ranges: &[],
-            });
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: None,
-                layout: Some(&pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader_module,
-                    entry_point: "vs_main",
-                    buffers: &[wgpu::VertexBufferLayout {
-                        array_stride: Vertex::min_size().get(),
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &[wgpu::VertexAttribute {
-                            offset: Vertex::METADATA.offset(0),
-                            shader_location: 0,
-                            format: wgpu::VertexFormat::Float32x2,
-                        }],
-                    }],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader_module,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Bgra8UnormSrgb, // TODO: check if color channels messed up?
-                        blend: Some(wgpu::BlendState {
-                            color: wgpu::BlendComponent {
-                                src_factor: wgpu::BlendFactor::One,
-                                dst_factor: wgpu::BlendFactor::One,
-                                operation: wgpu::BlendOperation::Add,
-                            },
-                            alpha: wgpu::BlendComponent {
-                                src_factor: wgpu::BlendFactor::One,
-                                dst_factor: wgpu::BlendFactor::One,
-                                operation: wgpu::BlendOperation::Add,
-                            },
-                        }),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    // Setting this to anything other than Fill requires Features::POLYGON_MODE_LINE
-                    // or Features::POLYGON_MODE_POINT
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    // Requires Features::DEPTH_CLIP_CONTROL
-                    unclipped_depth: false,
-                    // Requires Features::CONSERVATIVE_RASTERIZATION
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-            })
-        })
-    }
-}
+impl Lifecycle for ShapeMobject {
+    type Signal = Time;
+    type Resource = ShapePresentation;
 
-impl MobjectPresentation for PlanarTrianglesPresentation {
-    fn draw<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>) {
-        render_pass.set_pipeline(self.pipeline);
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[]);
-        render_pass.set_bind_group(1, &self.paint_bind_group, &[]);
-        render_pass.set_bind_group(2, &self.camera_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-        render_pass.draw(0..3, 0..1);
+    fn setup(&self, device: &wgpu::Device, _queue: &wgpu::Queue) -> Self::Resource {
+        ShapePresentation {
+            shape: self.clone(),
+            layer: planar_layer(device, std::iter::once(self)),
+        }
     }
-}
-
-pub struct VecPlanarTrianglesPresentation(Vec<PlanarTrianglesPresentation>);
 
-impl FromIterator<PlanarTrianglesPresentation> for VecPlanarTrianglesPresentation {
-    fn from_iter<I>(iter: I) -> Self
-    where
-        I: IntoIterator<Item = PlanarTrianglesPresentation>,
-    {
-        Self(iter.into_iter().collect())
+    fn prepare(
+        &self,
+        _signal: Self::Signal,
+        resource: &mut Self::Resource,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        if resource.shape != *self {
+            *resource = self.setup(device, queue);
+        }
     }
-}
 
-impl IntoIterator for VecPlanarTrianglesPresentation {
-    type Item = PlanarTrianglesPresentation;
-    type IntoIter = <Vec<PlanarTrianglesPresentation> as IntoIterator>::IntoIter;
-
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+    fn render(&self, resource: &Self::Resource, render_pass: &mut wgpu::RenderPass<'_>) {
+        resource.layer.render(render_pass);
     }
 }
 
-impl MobjectPresentation for VecPlanarTrianglesPresentation {
-    fn draw<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>) {
-        for planar_triangles_presentation in &self.0 {
-            planar_triangles_presentation.draw(render_pass);
+impl ShapeMobject {
+    pub(crate) fn faded(&self, opacity: f32) -> Self {
+        Self {
+            fill: self.fill.as_ref().map(|fill| fill.faded(opacity)),
+            stroke: self.stroke.as_ref().map(|stroke| stroke.faded(opacity)),
+            ..self.clone()
         }
     }
-}
 
-impl Mobject for ShapeMobject {
-    type MobjectPresentation = VecPlanarTrianglesPresentation;
-
-    fn presentation(&self, device: &wgpu::Device) -> Self::MobjectPresentation {
+    // Triangles of the fill and then of the stroke, each with its paint.
+    pub(crate) fn trimeshes(
+        &self,
+    ) -> impl Iterator<Item = (lyon::tessellation::VertexBuffers<Vertex, u32>, &Paint)> {
         std::iter::empty()
             .chain(self.fill.iter().map(|fill| {
                 let lyon_path = self.path.to_lyon_path();
@@ -192,9 +131,11 @@ impl Mobject for ShapeMobject {
                 let mut vertex_builder =
                     BuffersBuilder::new(&mut vertex_buffers, VertexConstructor);
                 let mut tessellator = lyon::tessellation::FillTessellator::new();
-                assert!(tessellator
-                    .tessellate(lyon_path.iter(), &fill.options, &mut vertex_builder)
-                    .is_ok());
+                assert!(
+                    tessellator
+                        .tessellate(lyon_path.iter(), &fill.options, &mut vertex_builder)
+                        .is_ok()
+                );
                 (vertex_buffers, &fill.paint)
             }))
             .chain(self.stroke.iter().map(|stroke| {
@@ -208,105 +149,50 @@ impl Mobject for ShapeMobject {
                 let mut vertex_builder =
                     BuffersBuilder::new(&mut vertex_buffers, VertexConstructor);
                 let mut tessellator = lyon::tessellation::StrokeTessellator::new();
-                assert!(tessellator
-                    .tessellate(lyon_path.iter(), &stroke.options, &mut vertex_builder)
-                    .is_ok());
+                assert!(
+                    tessellator
+                        .tessellate(lyon_path.iter(), &stroke.options, &mut vertex_builder)
+                        .is_ok()
+                );
                 (vertex_buffers, &stroke.paint)
             }))
-            .map(|(vertex_buffers, paint)| {
-                let pipeline = PlanarTrianglesPresentation::pipeline(device);
-
-                // buffers
-                let transform_shader_types = self.transform.to_shader_types();
-                let transform_buffers = transform_shader_types.initialize_buffers(device);
-                let transform_bind_group =
-                    TransformShaderTypes::bind_group_from_buffers(device, &transform_buffers);
-
-                let camera_shader_types = Camera::default().to_shader_types();
-                let camera_buffers = camera_shader_types.initialize_buffers(device);
-                let camera_bind_group =
-                    CameraShaderTypes::bind_group_from_buffers(device, &camera_buffers);
-
-                let paint_shader_types = paint.to_shader_types();
-                let paint_buffers = paint_shader_types.initialize_buffers(device);
-                let paint_bind_group =
-                    PaintShaderTypes::bind_group_from_buffers(device, &paint_buffers);
-
-                // TODO
-                let vertex_buffer = {
-                    let mut buffer = encase::StorageBuffer::new(Vec::<u8>::new());
-                    buffer.write(&vertex_buffers.vertices).unwrap();
-                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: None,
-                        contents: buffer.as_ref(),
-                        usage: wgpu::BufferUsages::VERTEX,
-                    })
-                };
-                let index_buffer = {
-                    let mut buffer = encase::StorageBuffer::new(Vec::<u8>::new());
-                    buffer.write(&vertex_buffers.indices).unwrap();
-                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: None,
-                        contents: buffer.as_ref(),
-                        usage: wgpu::BufferUsages::INDEX,
-                    })
-                }; // TODO
+    }
+}
 
-                PlanarTrianglesPresentation {
-                    pipeline,
-                    transform_bind_group,
-                    paint_bind_group,
-                    camera_bind_group,
-                    vertex_buffer,
-                    index_buffer,
-                }
+// Shapes seen through the default planar camera.
+pub(crate) fn planar_layer<'a>(
+    device: &wgpu::Device,
+    mobjects: impl IntoIterator<Item = &'a ShapeMobject>,
+) -> BuiltinPlanarLayer {
+    BuiltinPlanarLayer {
+        camera_transform_2d: vec![CameraTransform2DPresentation::new(
+            device,
+            &PlanarCamera::default().camera_transform_2d(),
+        )],
+        planar_trimesh: mobjects
+            .into_iter()
+            .flat_map(|mobject| {
+                mobject.trimeshes().map(|(vertex_buffers, paint)| {
+                    PlanarTrimeshPresentation::new(
+                        device,
+                        &vertex_buffers,
+                        &mobject.transform,
+                        paint,
+                    )
+                })
             })
-            .collect()
+            .collect(),
     }
-
-    // let mut encoder = renderer
-    //     .device
-    //     .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-    // let frame = renderer.surface.get_current_texture().unwrap();
-    // let frame_view = frame
-    //     .texture
-    //     .create_view(&wgpu::TextureViewDescriptor::default());
-    // {
-    //     let mut frame_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-    //         label: None,
-    //         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-    //             view: &frame_view,
-    //             resolve_target: None,
-    //             ops: wgpu::Operations {
-    //                 load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-    //                 store: wgpu::StoreOp::Store,
-    //             },
-    //         })],
-    //         depth_stencil_attachment: None,
-    //         timestamp_writes: None,
-    //         occlusion_query_set: None,
-    //     });
-    //     frame_pass.set_pipeline(pipeline);
-    //     frame_pass.set_bind_group(0, transform_bind_group, &[]);
-    //     frame_pass.set_bind_group(1, paint_bind_group, &[]);
-    //     frame_pass.set_bind_group(2, camera_bind_group, &[]);
-    //     frame_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-    //     frame_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-    //     frame_pass.draw(0..3, 0..1);
-    // }
-
-    // renderer.queue.submit(Some(encoder.finish()));
-    // frame.present();
 }
 
 // TODO: port ctors from bezier_rs::Subpath
 
 pub struct Rect(pub nalgebra::Vector2<f64>);
 
-impl MobjectBuilder for Rect {
+impl<C> MobjectBuilder<C> for Rect {
     type Instantiation = ShapeMobject;
 
-    fn instantiate(self, _config: &Config) -> Self::Instantiation {
+    fn instantiate(self, _config: &C) -> Self::Instantiation {
         ShapeMobject {
             transform: Transform::default(),
             path: Path::from_iter(std::iter::once(bezier_rs::Subpath::new_rect(
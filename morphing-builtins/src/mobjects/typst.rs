@@ -1,14 +1,19 @@
-use core::range::Range;
 use std::borrow::Cow;
-use std::ops::Deref;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::ops::Range;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
 
-use comemo::Track;
 use itertools::Itertools;
-use morphing_core::config::Config;
-use morphing_core::config::ConfigField;
-use morphing_core::traits::Mobject;
-use morphing_core::traits::MobjectBuilder;
+use morphing_core::ConfigSchema;
+use morphing_core::Interpolate;
+use morphing_core::Layer;
+use morphing_core::Lifecycle;
+use morphing_core::MobjectBuilder;
+use morphing_core::Time;
 use ttf_parser::OutlineBuilder;
 
 use super::super::components::color::Color;
@@ -20,29 +25,59 @@ use super::super::components::path::PathBuilder;
 use super::super::components::stroke::DashPattern;
 use super::super::components::stroke::Stroke;
 use super::super::components::transform::Transform;
+use super::super::layers::builtin_planar::BuiltinPlanarLayer;
 use super::shape::ShapeMobject;
-use super::shape::VecPlanarTrianglesPresentation;
+use super::shape::planar_layer;
 
 // Modified from typst/lib.rs, typst-cli/src/world.rs
 
-#[derive(serde::Deserialize)]
-struct TypstWorldInput {
-    inputs: Vec<(String, String)>,
-    include_system_fonts: bool,
-    include_embedded_fonts: bool,
-    font_paths: Vec<PathBuf>,
+// The `[typst]` table, with fallbacks in `configs/typst.toml`.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+pub struct TypstWorldInput {
+    pub inputs: Vec<(String, String)>,
+    pub include_system_fonts: bool,
+    pub include_embedded_fonts: bool,
+    pub font_paths: Vec<PathBuf>,
+}
+
+inventory::submit! {
+    ConfigSchema {
+        key: "typst",
+        schema: |generator| generator.subschema_for::<TypstWorldInput>(),
+    }
+}
+
+// Fonts found on disk are only read once requested, as in typst-cli/src/fonts.rs.
+#[derive(Debug)]
+struct FontSlot {
+    path: Option<PathBuf>,
+    index: u32,
+    font: OnceLock<Option<typst::text::Font>>,
+}
+
+impl FontSlot {
+    fn get(&self) -> Option<typst::text::Font> {
+        self.font
+            .get_or_init(|| {
+                let data = std::fs::read(self.path.as_ref()?).ok()?;
+                typst::text::Font::new(data.into(), self.index)
+            })
+            .clone()
+    }
 }
 
 #[derive(Debug)]
 struct TypstWorld {
-    library: typst::utils::LazyHash<typst::Library>,
-    book: typst::utils::LazyHash<typst::text::FontBook>,
-    fonts: Vec<typst_kit::fonts::FontSlot>,
+    library: comemo::Prehashed<typst::Library>,
+    book: comemo::Prehashed<typst::text::FontBook>,
+    fonts: Vec<FontSlot>,
     main_id: typst::syntax::FileId,
 }
 
 impl TypstWorld {
-    fn new(typst_world_input: TypstWorldInput) -> Self {
+    fn new(typst_world_input: &TypstWorldInput) -> Self {
         let inputs = typst_world_input
             .inputs
             .iter()
@@ -53,20 +88,70 @@ impl TypstWorld {
                 )
             })
             .collect();
-        let fonts = typst_kit::fonts::FontSearcher::new()
-            .include_system_fonts(typst_world_input.include_system_fonts)
-            .include_embedded_fonts(typst_world_input.include_embedded_fonts)
-            .search_with(typst_world_input.font_paths);
+
+        let mut database = fontdb::Database::new();
+        for font_path in &typst_world_input.font_paths {
+            database.load_fonts_dir(font_path);
+        }
+        if typst_world_input.include_system_fonts {
+            database.load_system_fonts();
+        }
+        let mut book = typst::text::FontBook::new();
+        let mut fonts = Vec::new();
+        for face in database.faces() {
+            let path = match &face.source {
+                fontdb::Source::File(path) | fontdb::Source::SharedFile(path, _) => path,
+                fontdb::Source::Binary(_) => continue,
+            };
+            if let Some(info) = database
+                .with_face_data(face.id, typst::text::FontInfo::new)
+                .flatten()
+            {
+                book.push(info);
+                fonts.push(FontSlot {
+                    path: Some(path.clone()),
+                    index: face.index,
+                    font: OnceLock::new(),
+                });
+            }
+        }
+        if typst_world_input.include_embedded_fonts {
+            for data in typst_assets::fonts() {
+                for (index, font) in
+                    typst::text::Font::iter(typst::foundations::Bytes::from_static(data))
+                        .enumerate()
+                {
+                    book.push(font.info().clone());
+                    fonts.push(FontSlot {
+                        path: None,
+                        index: index as u32,
+                        font: OnceLock::from(Some(font)),
+                    });
+                }
+            }
+        }
+
         Self {
-            library: typst::utils::LazyHash::new(
-                typst::Library::builder().with_inputs(inputs).build(),
-            ),
-            book: typst::utils::LazyHash::new(fonts.book),
-            fonts: fonts.fonts,
+            library: comemo::Prehashed::new(typst::Library::builder().with_inputs(inputs).build()),
+            book: comemo::Prehashed::new(book),
+            fonts,
             main_id: typst::syntax::FileId::new_fake(typst::syntax::VirtualPath::new("main.typ")),
         }
     }
 
+    // Searching fonts takes a while, so worlds are shared by all mobjects with the same input.
+    fn shared(typst_world_input: &TypstWorldInput) -> Arc<Self> {
+        static TYPST_WORLDS: OnceLock<Mutex<HashMap<TypstWorldInput, Arc<TypstWorld>>>> =
+            OnceLock::new();
+        TYPST_WORLDS
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap()
+            .entry(typst_world_input.clone())
+            .or_insert_with(|| Arc::new(Self::new(typst_world_input)))
+            .clone()
+    }
+
     fn source(&self, text: String) -> typst::syntax::Source {
         typst::syntax::Source::new(self.main_id, text)
     }
@@ -86,71 +171,43 @@ impl TypstWorld {
             panic!("Typst error. See diagnostics above.");
         }
 
-        let styles = typst::foundations::StyleChain::new(&self.library().styles);
-        let traced = typst::engine::Traced::default();
-        let introspector = typst::introspection::Introspector::default();
-        let world = self.track();
-        let traced = traced.track();
-        let introspector = introspector.track();
-
-        let mut sink = typst::engine::Sink::new();
-        let content = typst::eval::eval(
-            world,
-            traced,
-            sink.track_mut(),
-            typst::engine::Route::default().track(),
-            source,
+        let mut tracer = typst::eval::Tracer::new();
+        typst::compile(
+            &TypstSourceWorld {
+                world: self,
+                source,
+            },
+            &mut tracer,
         )
-        .unwrap_or_else(|errors| {
-            sink.delay(errors);
-            eprint_diagnostics(sink.delayed());
-        })
-        .content();
-
-        let mut engine = typst::engine::Engine {
-            world,
-            introspector,
-            traced,
-            sink: sink.track_mut(),
-            route: typst::engine::Route::default(),
-        };
-        let document = typst::layout::layout_document(&mut engine, &content, styles)
-            .unwrap_or_else(|errors| {
-                sink.delay(errors);
-                eprint_diagnostics(sink.delayed());
-            });
-
-        let delayed = sink.delayed();
-        if !delayed.is_empty() {
-            eprint_diagnostics(delayed);
-        }
-        document
+        .unwrap_or_else(|errors| eprint_diagnostics(errors))
     }
 }
 
-impl Deref for TypstWorld {
-    type Target = dyn typst::World;
-
-    fn deref(&self) -> &Self::Target {
-        self
-    }
+// Typst asks the world for its main source, so every document is compiled in a world of its own.
+struct TypstSourceWorld<'w> {
+    world: &'w TypstWorld,
+    source: &'w typst::syntax::Source,
 }
 
-impl typst::World for TypstWorld {
-    fn library(&self) -> &typst::utils::LazyHash<typst::Library> {
-        &self.library
+impl typst::World for TypstSourceWorld<'_> {
+    fn library(&self) -> &comemo::Prehashed<typst::Library> {
+        &self.world.library
     }
 
-    fn book(&self) -> &typst::utils::LazyHash<typst::text::FontBook> {
-        &self.book
+    fn book(&self) -> &comemo::Prehashed<typst::text::FontBook> {
+        &self.world.book
     }
 
-    fn main(&self) -> typst::syntax::FileId {
-        self.main_id
+    fn main(&self) -> typst::syntax::Source {
+        self.source.clone()
     }
 
-    fn source(&self, _id: typst::syntax::FileId) -> typst::diag::FileResult<typst::syntax::Source> {
-        Err(typst::diag::FileError::AccessDenied)
+    fn source(&self, id: typst::syntax::FileId) -> typst::diag::FileResult<typst::syntax::Source> {
+        if id == self.source.id() {
+            Ok(self.source.clone())
+        } else {
+            Err(typst::diag::FileError::AccessDenied)
+        }
     }
 
     fn file(
@@ -161,7 +218,7 @@ impl typst::World for TypstWorld {
     }
 
     fn font(&self, index: usize) -> Option<typst::text::Font> {
-        self.fonts[index].get()
+        self.world.fonts[index].get()
     }
 
     fn today(&self, _: Option<i64>) -> Option<typst::foundations::Datetime> {
@@ -169,14 +226,6 @@ impl typst::World for TypstWorld {
     }
 }
 
-impl ConfigField for TypstWorld {
-    const PATH: &'static str = "typst";
-
-    fn parse(value: &toml::Value) -> Self {
-        Self::new(value.clone().try_into().unwrap())
-    }
-}
-
 pub struct Typst(String);
 
 impl Typst {
@@ -188,11 +237,15 @@ impl Typst {
     }
 }
 
-impl MobjectBuilder for Typst {
+// The `[typst]` table is taken from the scene config, e.g. from a field `typst: TypstWorldInput`.
+impl<C> MobjectBuilder<C> for Typst
+where
+    C: AsRef<TypstWorldInput>,
+{
     type Instantiation = TypstMobject;
 
-    fn instantiate(self, config: &Config) -> Self::Instantiation {
-        TypstMobject::instantiate(self.0, config)
+    fn instantiate(self, config: &C) -> Self::Instantiation {
+        TypstMobject::instantiate(self.0, config.as_ref())
     }
 }
 
@@ -209,17 +262,23 @@ pub struct TypstMobject {
 }
 
 impl TypstMobject {
-    fn instantiate(text: String, config: &Config) -> Self {
+    fn instantiate(text: String, typst_world_input: &TypstWorldInput) -> Self {
+        let typst_world = TypstWorld::shared(typst_world_input);
+        let source = typst_world.source(text.clone());
+        let document = typst_world.document(&source);
         Self {
-            text: text.clone(),
-            tokens: config.operate(|typst_world: &TypstWorld| {
-                let source = typst_world.source(text);
-                let document = typst_world.document(&source);
-                Self::from_typst_document(&document, &source)
-            }),
+            text,
+            tokens: Self::from_typst_document(&document, &source),
         }
     }
 
+    fn token_text(&self, token: &TypstMobjectToken) -> Option<&str> {
+        token
+            .span
+            .as_ref()
+            .and_then(|span| self.text.get(span.start..span.end))
+    }
+
     fn outline_glyph_to_path(font: &typst::text::Font, id: ttf_parser::GlyphId) -> Option<Path> {
         let mut builder = PathBuilder::new();
         font.ttf().outline_glyph(id, &mut builder)?;
@@ -230,13 +289,13 @@ impl TypstMobject {
         let mut builder = PathBuilder::new();
         for path_item in &path.0 {
             match path_item {
-                &typst::visualize::PathItem::MoveTo(start) => {
+                typst::visualize::PathItem::MoveTo(start) => {
                     builder.move_to(start.x.to_pt() as f32, start.y.to_pt() as f32)
                 }
-                &typst::visualize::PathItem::LineTo(end) => {
+                typst::visualize::PathItem::LineTo(end) => {
                     builder.line_to(end.x.to_pt() as f32, end.y.to_pt() as f32)
                 }
-                &typst::visualize::PathItem::CubicTo(handle_start, handle_end, end) => builder
+                typst::visualize::PathItem::CubicTo(handle_start, handle_end, end) => builder
                     .curve_to(
                         handle_start.x.to_pt() as f32,
                         handle_start.y.to_pt() as f32,
@@ -245,7 +304,7 @@ impl TypstMobject {
                         end.x.to_pt() as f32,
                         end.y.to_pt() as f32,
                     ),
-                &typst::visualize::PathItem::ClosePath => builder.close(),
+                typst::visualize::PathItem::ClosePath => builder.close(),
             }
         }
         builder.build()
@@ -263,7 +322,10 @@ impl TypstMobject {
                         let sample_points = match path.bounding_box() {
                             None => Vec::new(),
                             Some(
-                                [glam::DVec2 { x: x_min, y: y_min }, glam::DVec2 { x: x_max, y: y_max }],
+                                [
+                                    glam::DVec2 { x: x_min, y: y_min },
+                                    glam::DVec2 { x: x_max, y: y_max },
+                                ],
                             ) => Vec::from([
                                 glam::DVec2 { x: x_min, y: y_min },
                                 glam::DVec2 { x: x_min, y: y_max },
@@ -370,7 +432,6 @@ impl TypstMobject {
 
     fn from_path(
         path: Path,
-        fill_rule: Option<typst::visualize::FillRule>,
         fill: Option<&typst::visualize::Paint>,
         stroke: Option<&typst::visualize::FixedStroke>,
         span: typst::syntax::Span,
@@ -384,10 +445,7 @@ impl TypstMobject {
         ])); // TODO: pre transform or post transform
         let fill = fill.map(|fill| Fill {
             paint: Self::typst_paint_to_paint(fill, &path),
-            options: match fill_rule.unwrap_or_default() {
-                typst::visualize::FillRule::NonZero => lyon::tessellation::FillOptions::non_zero(),
-                typst::visualize::FillRule::EvenOdd => lyon::tessellation::FillOptions::even_odd(),
-            },
+            options: lyon::tessellation::FillOptions::non_zero(),
         });
         let stroke = stroke.map(|stroke| Stroke {
             dash_pattern: stroke.dash.as_ref().map(|dash_pattern| DashPattern {
@@ -449,20 +507,19 @@ impl TypstMobject {
         transform: typst::layout::Transform,
         source: &typst::syntax::Source,
     ) -> Vec<TypstMobjectToken> {
-        let typst_path = match &shape.geometry {
-            &typst::visualize::Geometry::Line(point) => {
+        let typst_path = match shape.geometry {
+            typst::visualize::Geometry::Line(point) => {
                 let mut path = typst::visualize::Path::new();
                 path.line_to(point);
                 Cow::Owned(path)
             }
-            &typst::visualize::Geometry::Rect(size) => {
+            typst::visualize::Geometry::Rect(size) => {
                 Cow::Owned(typst::visualize::Path::rect(size))
             }
-            &typst::visualize::Geometry::Path(ref path) => Cow::Borrowed(path),
+            typst::visualize::Geometry::Path(ref path) => Cow::Borrowed(path),
         };
         vec![Self::from_path(
             Self::typst_path_to_path(&typst_path),
-            Some(shape.fill_rule),
             shape.fill.as_ref(),
             shape.stroke.as_ref(),
             span,
@@ -502,7 +559,6 @@ impl TypstMobject {
                     .map(move |path| {
                         Self::from_path(
                             path,
-                            None,
                             Some(&text.fill),
                             text.stroke.as_ref(),
                             glyph.span.0,
@@ -551,7 +607,7 @@ impl TypstMobject {
                 let transform = transform
                     .pre_concat(typst::layout::Transform::translate(position.x, position.y));
                 match item {
-                    &typst::layout::FrameItem::Group(ref group) => {
+                    typst::layout::FrameItem::Group(group) => {
                         if group.clip_path.is_some() {
                             panic!("Clip path not supported");
                         }
@@ -561,17 +617,16 @@ impl TypstMobject {
                             source,
                         )
                     }
-                    &typst::layout::FrameItem::Text(ref text) => {
+                    typst::layout::FrameItem::Text(text) => {
                         Self::from_typst_text(text, transform, source)
                     }
-                    &typst::layout::FrameItem::Shape(ref shape, span) => {
-                        Self::from_typst_shape(shape, span, transform, source)
+                    typst::layout::FrameItem::Shape(shape, span) => {
+                        Self::from_typst_shape(shape, *span, transform, source)
                     }
-                    &typst::layout::FrameItem::Image(_, _, _) => {
+                    typst::layout::FrameItem::Image(_, _, _) => {
                         panic!("Unsopported item: image")
                     }
-                    &typst::layout::FrameItem::Link(..) => Vec::new(),
-                    &typst::layout::FrameItem::Tag(..) => Vec::new(),
+                    typst::layout::FrameItem::Meta(..) => Vec::new(),
                 }
             })
             .collect()
@@ -599,29 +654,135 @@ impl TypstMobject {
     }
 }
 
-impl Mobject for TypstMobject {
-    type MobjectPresentation = VecPlanarTrianglesPresentation;
+impl Lifecycle for TypstMobject {
+    type Signal = Time;
+    type Resource = BuiltinPlanarLayer;
+
+    fn setup(&self, device: &wgpu::Device, _queue: &wgpu::Queue) -> Self::Resource {
+        planar_layer(
+            device,
+            self.tokens
+                .iter()
+                .map(|TypstMobjectToken { mobject, .. }| mobject),
+        )
+    }
 
-    fn presentation(
+    fn prepare(
         &self,
-        device: &iced::widget::shader::wgpu::Device,
-    ) -> Self::MobjectPresentation {
-        self.tokens
+        _signal: Self::Signal,
+        _resource: &mut Self::Resource,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) {
+    }
+
+    fn render(&self, resource: &Self::Resource, render_pass: &mut wgpu::RenderPass<'_>) {
+        resource.render(render_pass);
+    }
+}
+
+// Morphs tokens into the tokens of another typst mobject with identical source text,
+// e.g. `$a + b$` into `$b + a$`.
+// The k-th occurrence of a text is matched with its k-th occurrence on the other side.
+// Unmatched tokens, including those without a span, fade out or in.
+pub struct TypstTransformMatching {
+    matched_mobjects: Vec<(ShapeMobject, ShapeMobject)>,
+    source_only_mobjects: Vec<ShapeMobject>,
+    target_only_mobjects: Vec<ShapeMobject>,
+}
+
+impl TypstTransformMatching {
+    pub fn new(source: &TypstMobject, target: &TypstMobject) -> Self {
+        let mut target_indices: HashMap<&str, VecDeque<usize>> = HashMap::new();
+        for (index, token) in target.tokens.iter().enumerate() {
+            if let Some(text) = target.token_text(token) {
+                target_indices.entry(text).or_default().push_back(index);
+            }
+        }
+        let mut target_matched = vec![false; target.tokens.len()];
+        let mut matched_mobjects = Vec::new();
+        let mut source_only_mobjects = Vec::new();
+        for token in &source.tokens {
+            match source
+                .token_text(token)
+                .and_then(|text| target_indices.get_mut(text)?.pop_front())
+            {
+                Some(index) => {
+                    target_matched[index] = true;
+                    matched_mobjects
+                        .push((token.mobject.clone(), target.tokens[index].mobject.clone()));
+                }
+                None => source_only_mobjects.push(token.mobject.clone()),
+            }
+        }
+        let target_only_mobjects = target
+            .tokens
             .iter()
-            .flat_map(|TypstMobjectToken { mobject, .. }| mobject.presentation(device))
-            .collect()
+            .zip(target_matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(token, _)| token.mobject.clone())
+            .collect();
+        Self {
+            matched_mobjects,
+            source_only_mobjects,
+            target_only_mobjects,
+        }
+    }
+
+    fn mobjects(&self, alpha: f32) -> impl Iterator<Item = ShapeMobject> + '_ {
+        self.matched_mobjects
+            .iter()
+            .map(move |(source, target)| source.interpolate(target, alpha))
+            .chain(
+                self.source_only_mobjects
+                    .iter()
+                    .map(move |source| source.faded(1.0 - alpha)),
+            )
+            .chain(
+                self.target_only_mobjects
+                    .iter()
+                    .map(move |target| target.faded(alpha)),
+            )
+    }
+}
+
+impl Lifecycle for TypstTransformMatching {
+    type Signal = Time;
+    type Resource = BuiltinPlanarLayer;
+
+    // Geometry changes every frame, so presentations are rebuilt in `prepare`.
+    fn setup(&self, _device: &wgpu::Device, _queue: &wgpu::Queue) -> Self::Resource {
+        BuiltinPlanarLayer::default()
+    }
+
+    fn prepare(
+        &self,
+        signal: Self::Signal,
+        resource: &mut Self::Resource,
+        device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) {
+        *resource = planar_layer(
+            device,
+            &self.mobjects(signal.clamp(0.0, 1.0)).collect::<Vec<_>>(),
+        );
+    }
+
+    fn render(&self, resource: &Self::Resource, render_pass: &mut wgpu::RenderPass<'_>) {
+        resource.render(render_pass);
     }
 }
 
 #[cfg(test)]
 mod typst_tests {
     use super::TypstMobject;
+    use super::TypstTransformMatching;
     use super::TypstWorld;
     use super::TypstWorldInput;
 
     #[test]
     fn test_typst_mobject() {
-        let typst_world = TypstWorld::new(TypstWorldInput {
+        let typst_world = TypstWorld::new(&TypstWorldInput {
             inputs: Vec::new(),
             include_system_fonts: true,
             include_embedded_fonts: true,
@@ -643,4 +804,27 @@ mod typst_tests {
         // let svg = typst_svg::svg_merged(&document, typst::layout::Abs::zero());
         // println!("{svg}");
     }
+
+    #[test]
+    fn test_typst_transform_matching() {
+        let typst_world = TypstWorld::new(&TypstWorldInput {
+            inputs: Vec::new(),
+            include_system_fonts: true,
+            include_embedded_fonts: true,
+            font_paths: Vec::new(),
+        });
+        let typst_mobject = |text: &str| {
+            let source = typst_world.source(text.to_string());
+            let document = typst_world.document(&source);
+            TypstMobject {
+                text: text.to_string(),
+                tokens: TypstMobject::from_typst_document(&document, &source),
+            }
+        };
+        let transform_matching =
+            TypstTransformMatching::new(&typst_mobject("$a + b$"), &typst_mobject("$b + a + c$"));
+        assert_eq!(transform_matching.matched_mobjects.len(), 3);
+        assert_eq!(transform_matching.source_only_mobjects.len(), 0);
+        assert_eq!(transform_matching.target_only_mobjects.len(), 2);
+    }
 }
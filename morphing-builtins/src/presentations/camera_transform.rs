@@ -1,7 +1,26 @@
+use morphing_core::Component;
+use morphing_core::ComponentShaderTypes;
+
+use super::super::components::camera_transform::CameraTransform2D;
+use super::super::components::camera_transform::CameraTransform2DShaderTypes;
+
 pub struct CameraTransform2DPresentation {
     pub camera_transform_2d_bind_group: wgpu::BindGroup,
 }
 
+impl CameraTransform2DPresentation {
+    pub fn new(device: &wgpu::Device, camera_transform_2d: &CameraTransform2D) -> Self {
+        let buffers = camera_transform_2d
+            .to_shader_types()
+            .new_buffers_initialized(device);
+        Self {
+            camera_transform_2d_bind_group: CameraTransform2DShaderTypes::bind_group_from_buffers(
+                device, &buffers,
+            ),
+        }
+    }
+}
+
 pub struct CameraTransform3DPresentation {
     pub camera_transform_3d_bind_group: wgpu::BindGroup,
 }
@@ -1,8 +1,62 @@
+use morphing_core::Component;
+use morphing_core::ComponentShaderTypes;
+use wgpu::util::DeviceExt;
+
+use super::super::components::paint::Paint;
+use super::super::components::paint::PaintShaderTypes;
+use super::super::components::transform::Transform;
+use super::super::components::transform::TransformShaderTypes;
+use super::super::layers::builtin_planar::Vertex;
+use super::super::layers::builtin_planar::builtin_planar_pipeline;
+
 pub struct PlanarTrimeshPresentation {
+    pub pipeline: &'static wgpu::RenderPipeline,
     pub transform_bind_group: wgpu::BindGroup,
     pub paint_bind_group: wgpu::BindGroup,
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+}
+
+impl PlanarTrimeshPresentation {
+    pub fn new(
+        device: &wgpu::Device,
+        vertex_buffers: &lyon::tessellation::VertexBuffers<Vertex, u32>,
+        transform: &Transform,
+        paint: &Paint,
+    ) -> Self {
+        let transform_buffers = transform.to_shader_types().new_buffers_initialized(device);
+        let paint_buffers = paint.to_shader_types().new_buffers_initialized(device);
+        let vertex_buffer = {
+            let mut buffer = encase::StorageBuffer::new(Vec::<u8>::new());
+            buffer.write(&vertex_buffers.vertices).unwrap();
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: buffer.as_ref(),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        };
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: &vertex_buffers
+                .indices
+                .iter()
+                .flat_map(|index| index.to_ne_bytes())
+                .collect::<Vec<_>>(),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        Self {
+            pipeline: builtin_planar_pipeline(device),
+            transform_bind_group: TransformShaderTypes::bind_group_from_buffers(
+                device,
+                &transform_buffers,
+            ),
+            paint_bind_group: PaintShaderTypes::bind_group_from_buffers(device, &paint_buffers),
+            vertex_buffer,
+            index_buffer,
+            index_count: vertex_buffers.indices.len() as u32,
+        }
+    }
 }
 
 pub struct PlanarTrimeshVecPresentation(pub Vec<PlanarTrimeshPresentation>);
@@ -10,13 +10,11 @@ const PI = radians(180.0);
 //     scale: f32,
 // }
 
-struct CameraTransform2DUniform {
-    view_motor: vec4<f32>,
-    projection_matrix: mat3x3<f32>,
-}
+// Checked against `TransformShaderTypes`, `PaintShaderTypes` and `CameraTransform2DShaderTypes`
+// by `include_wgsl!` while compiling.
 
-struct Transform2DUniform {
-    motor: vec4<f32>,
+struct TransformUniform {
+    motor: mat2x4<f32>,
     scale: f32,
 }
 
@@ -38,6 +36,11 @@ struct GradientStopStorage {
     color: vec4<f32>,
 }
 
+struct CameraTransform2DUniform {
+    view_motor: vec4<f32>,
+    projection_matrix: mat3x3<f32>,
+}
+
 struct Vertex {
     @location(0) position: vec2<f32>,
 }
@@ -47,7 +50,7 @@ struct VertexOutput {
     @location(0) position: vec2<f32>,
 }
 
-@group(0) @binding(0) var<uniform> u_transform_2d: Transform2DUniform;
+@group(0) @binding(0) var<uniform> u_transform: TransformUniform;
 @group(1) @binding(0) var<uniform> u_color: ColorUniform;
 @group(1) @binding(1) var<storage> s_gradients: array<GradientStorage>;
 @group(1) @binding(2) var<storage> s_radial_stops: array<GradientStopStorage>;
@@ -60,8 +63,8 @@ struct VertexOutput {
 // ) -> VertexOutput {
 //     return VertexOutput(
 //         vec4(apply_projection_matrix(
-//             u_camera_transform_3d.projection_matrix, apply_motor(
-//                 u_camera_transform_3d.motor, apply_motor(
+//             u_camera_transform_3d.projection_matrix, apply_motor_3d(
+//                 u_camera_transform_3d.motor, apply_motor_3d(
 //                     u_transform_3d.motor, in.position
 //                 )
 //             )
@@ -78,17 +81,17 @@ struct VertexOutput {
 //     return homogeneous_position.xyz / homogeneous_position.w;
 // }
 
-// // https://github.com/enkimute/LookMaNoMatrices/blob/main/src/miniPGA.glsl
-// // Port from function `sw_mp`
+// https://github.com/enkimute/LookMaNoMatrices/blob/main/src/miniPGA.glsl
+// Port from function `sw_mp`
 // Motor should be normalized.
-// fn apply_motor(
-//     motor: mat2x4<f32>,
-//     position: vec3<f32>,
-// ) -> vec3<f32> {
-//     let direction = cross(position, motor[0].yzw) - motor[1].yzw;
-//     let half_shift = motor[0].x * direction + cross(direction, motor[0].yzw) - motor[0].yzw * motor[1].x;
-//     return half_shift * 2.0 + position;
-// }
+fn apply_motor_3d(
+    motor: mat2x4<f32>,
+    position: vec3<f32>,
+) -> vec3<f32> {
+    let direction = cross(position, motor[0].yzw) - motor[1].yzw;
+    let half_shift = motor[0].x * direction + cross(direction, motor[0].yzw) - motor[0].yzw * motor[1].x;
+    return half_shift * 2.0 + position;
+}
 
 @vertex
 fn vs_main(
@@ -96,10 +99,10 @@ fn vs_main(
 ) -> VertexOutput {
     return VertexOutput(
         vec4(apply_projection_matrix(
-            u_camera_transform_2d.projection_matrix, apply_motor(
-                u_camera_transform_2d.motor, apply_motor(
-                    u_transform_2d.motor, in.position
-                )
+            u_camera_transform_2d.projection_matrix, apply_motor_2d(
+                u_camera_transform_2d.view_motor, apply_motor_3d(
+                    u_transform.motor, vec3(in.position * u_transform.scale, 0.0)
+                ).xy
             )
         ), 0.0, 1.0),
         in.position,
@@ -109,18 +112,24 @@ fn vs_main(
 fn apply_projection_matrix(
     projection_matrix: mat3x3<f32>,
     position: vec2<f32>,
-) -> vec3<f32> {
+) -> vec2<f32> {
     let homogeneous_position = projection_matrix * vec3(position, 1.0);
     return homogeneous_position.xy / homogeneous_position.z;
 }
 
-// Motor should be normalized.
-fn apply_motor(
+// Sandwich product with the motor `(1, e12, e01, -e02)` of `geometric_algebra::ppga2d`,
+// matching `apply_motor_2d` of the CPU rasterizer.
+fn apply_motor_2d(
     motor: vec4<f32>,
     position: vec2<f32>,
 ) -> vec2<f32> {
-    let half_shift = (motor.w - motor.x * position.x) * vec2(motor.y, motor.x) + (motor.z - motor.y * position.y) * vec2(-motor.x, motor.y);
-    return half_shift * 2.0 + position;
+    let rotated = (motor.x * motor.x - motor.y * motor.y) * position
+        + 2.0 * motor.x * motor.y * vec2(-position.y, position.x);
+    let shift = 2.0 * vec2(
+        motor.x * motor.z - motor.y * motor.w,
+        motor.y * motor.z + motor.x * motor.w,
+    );
+    return (rotated + shift) / dot(motor.xy, motor.xy);
 }
 
 @fragment
@@ -170,11 +179,10 @@ fn interpolate_radial_color(
     alpha: f32,
 ) -> vec4<f32> {
     var start = stops_range[0];
-    var end = stops_range[1];
-
-    if (start == end) {
+    if (stops_range[0] == stops_range[1]) {
         return vec4(1.0);
     }
+    var end = stops_range[1] - 1u;
     if (alpha < s_radial_stops[start].alpha) {
         return s_radial_stops[start].color;
     }
@@ -200,11 +208,10 @@ fn interpolate_angular_color(
     alpha: f32,
 ) -> vec4<f32> {
     var start = stops_range[0];
-    var end = stops_range[1];
-
-    if (start == end) {
+    if (stops_range[0] == stops_range[1]) {
         return vec4(1.0);
     }
+    var end = stops_range[1] - 1u;
     if (alpha < s_angular_stops[start].alpha) {
         return s_angular_stops[start].color;
     }
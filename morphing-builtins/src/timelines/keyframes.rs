@@ -0,0 +1,296 @@
+use morphing_core::DenormalizedTimeMetric;
+use morphing_core::IncreasingRate;
+use morphing_core::NormalizedTimeMetric;
+use morphing_core::Rate;
+use morphing_core::Time;
+use morphing_core::TimeMetric;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyframeInterpolation {
+    Linear,
+    // Cubic Hermite with Fritsch-Carlson tangents, which never overshoot monotonic keyframes.
+    Hermite,
+    // Cubic Hermite with finite difference tangents, which may overshoot.
+    CatmullRom,
+}
+
+#[derive(Debug)]
+pub enum KeyframeRateError {
+    Empty,
+    NotFinite { index: usize },
+    NotSorted { index: usize },
+    NotIncreasing { index: usize },
+}
+
+impl std::fmt::Display for KeyframeRateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Keyframes should not be empty"),
+            Self::NotFinite { index } => write!(f, "Keyframe {index} is not finite"),
+            Self::NotSorted { index } => {
+                write!(f, "Keyframe {index} is not strictly after its predecessor")
+            }
+            Self::NotIncreasing { index } => {
+                write!(
+                    f,
+                    "Curve decreases within the segment starting at keyframe {index}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyframeRateError {}
+
+#[derive(serde::Deserialize)]
+struct KeyframeRateData {
+    interpolation: KeyframeInterpolation,
+    keyframes: Vec<(Time, f32)>,
+}
+
+// Rate defined by `(t, value)` keyframes, deserialized from e.g.
+// `{ interpolation = "hermite", keyframes = [[0.0, 0.0], [0.6, 0.9], [1.0, 1.0]] }`.
+// The values of the first and last keyframes are held outside the keyframe range.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "KeyframeRateData")]
+pub struct KeyframeRate {
+    interpolation: KeyframeInterpolation,
+    keyframes: Vec<(Time, f32)>,
+    #[serde(skip)]
+    tangents: Vec<f32>,
+}
+
+impl TryFrom<KeyframeRateData> for KeyframeRate {
+    type Error = KeyframeRateError;
+
+    fn try_from(data: KeyframeRateData) -> Result<Self, Self::Error> {
+        Self::new(data.interpolation, data.keyframes)
+    }
+}
+
+impl KeyframeRate {
+    pub fn new(
+        interpolation: KeyframeInterpolation,
+        keyframes: Vec<(Time, f32)>,
+    ) -> Result<Self, KeyframeRateError> {
+        if keyframes.is_empty() {
+            return Err(KeyframeRateError::Empty);
+        }
+        if let Some(index) = keyframes
+            .iter()
+            .position(|(t, value)| !t.is_finite() || !value.is_finite())
+        {
+            return Err(KeyframeRateError::NotFinite { index });
+        }
+        if let Some(index) = keyframes
+            .windows(2)
+            .position(|window| window[0].0 >= window[1].0)
+        {
+            return Err(KeyframeRateError::NotSorted { index: index + 1 });
+        }
+        let tangents = Self::tangents(interpolation, &keyframes);
+        Ok(Self {
+            interpolation,
+            keyframes,
+            tangents,
+        })
+    }
+
+    // Checks that the curve never decreases, so that it may be used where an `IncreasingRate` is required.
+    // Tangents overshooting an increasing segment are scaled down into its monotonicity region,
+    // following Fritsch & Carlson (1980), while segments between decreasing keyframes are rejected.
+    pub fn increasing(mut self) -> Result<IncreasingKeyframeRate, KeyframeRateError> {
+        for index in 0..self.keyframes.len() - 1 {
+            let ((t0, v0), (t1, v1)) = (self.keyframes[index], self.keyframes[index + 1]);
+            let secant = (v1 - v0) / (t1 - t0);
+            let (m0, m1) = (self.tangents[index], self.tangents[index + 1]);
+            let increasing = if secant < 0.0 {
+                false
+            } else if secant == 0.0 {
+                m0 == 0.0 && m1 == 0.0
+            } else {
+                m0 >= 0.0 && m1 >= 0.0
+            };
+            if !increasing {
+                return Err(KeyframeRateError::NotIncreasing { index });
+            }
+            if secant > 0.0 && !Self::monotonic(m0 / secant, m1 / secant) {
+                // Onto the circle of radius 3, which lies within the region.
+                let tau = 3.0 * secant / m0.hypot(m1);
+                self.tangents[index] = tau * m0;
+                self.tangents[index + 1] = tau * m1;
+            }
+        }
+        Ok(IncreasingKeyframeRate(self))
+    }
+
+    // Whether a cubic Hermite segment with non-negative tangents `alpha` and `beta`,
+    // relative to its secant, is monotonic (Fritsch & Carlson, 1980).
+    // Scaling both tangents down never leaves the region.
+    fn monotonic(alpha: f32, beta: f32) -> bool {
+        let (a, b, c) = (
+            alpha + beta - 2.0,
+            2.0 * alpha + beta - 3.0,
+            alpha + 2.0 * beta - 3.0,
+        );
+        a <= 0.0 || b <= 0.0 || c <= 0.0 || alpha - b * b / (3.0 * a) >= 0.0
+    }
+
+    fn tangents(interpolation: KeyframeInterpolation, keyframes: &[(Time, f32)]) -> Vec<f32> {
+        let secants: Vec<f32> = keyframes
+            .windows(2)
+            .map(|window| (window[1].1 - window[0].1) / (window[1].0 - window[0].0))
+            .collect();
+        let Some((first, last)) = secants.first().zip(secants.last()) else {
+            return vec![0.0; keyframes.len()];
+        };
+        let interior = keyframes
+            .windows(3)
+            .zip(secants.windows(2))
+            .map(|(window, secants)| {
+                match interpolation {
+                    KeyframeInterpolation::Linear => 0.0,
+                    KeyframeInterpolation::Hermite => {
+                        if secants[0] * secants[1] <= 0.0 {
+                            0.0
+                        } else {
+                            // Weighted harmonic mean of neighboring secants.
+                            let (h0, h1) = (window[1].0 - window[0].0, window[2].0 - window[1].0);
+                            let (w0, w1) = (2.0 * h1 + h0, h1 + 2.0 * h0);
+                            (w0 + w1) / (w0 / secants[0] + w1 / secants[1])
+                        }
+                    }
+                    KeyframeInterpolation::CatmullRom => {
+                        (window[2].1 - window[0].1) / (window[2].0 - window[0].0)
+                    }
+                }
+            });
+        std::iter::once(*first)
+            .chain(interior)
+            .chain(std::iter::once(*last))
+            .collect()
+    }
+
+    fn eval_time(&self, t: Time) -> f32 {
+        let index = self.keyframes.partition_point(|(key_t, _)| *key_t <= t);
+        if index == 0 {
+            return self.keyframes[0].1;
+        }
+        if index == self.keyframes.len() {
+            return self.keyframes[index - 1].1;
+        }
+        let ((t0, v0), (t1, v1)) = (self.keyframes[index - 1], self.keyframes[index]);
+        let h = t1 - t0;
+        let s = (t - t0) / h;
+        match self.interpolation {
+            KeyframeInterpolation::Linear => v0 + (v1 - v0) * s,
+            KeyframeInterpolation::Hermite | KeyframeInterpolation::CatmullRom => {
+                let (m0, m1) = (self.tangents[index - 1], self.tangents[index]);
+                let (s2, s3) = (s * s, s * s * s);
+                (2.0 * s3 - 3.0 * s2 + 1.0) * v0
+                    + (s3 - 2.0 * s2 + s) * h * m0
+                    + (-2.0 * s3 + 3.0 * s2) * v1
+                    + (s3 - s2) * h * m1
+            }
+        }
+    }
+}
+
+// A `KeyframeRate` validated to never decrease.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "KeyframeRate")]
+pub struct IncreasingKeyframeRate(KeyframeRate);
+
+impl TryFrom<KeyframeRate> for IncreasingKeyframeRate {
+    type Error = KeyframeRateError;
+
+    fn try_from(rate: KeyframeRate) -> Result<Self, Self::Error> {
+        rate.increasing()
+    }
+}
+
+impl<TM> Rate<TM> for KeyframeRate
+where
+    TM: TimeMetric,
+{
+    type OutputTimeMetric = TM;
+
+    fn eval(&self, time_metric: TM) -> Self::OutputTimeMetric {
+        self.eval_time(*time_metric).into()
+    }
+}
+
+impl<TM> Rate<TM> for IncreasingKeyframeRate
+where
+    TM: TimeMetric,
+{
+    type OutputTimeMetric = TM;
+
+    fn eval(&self, time_metric: TM) -> Self::OutputTimeMetric {
+        self.0.eval(time_metric)
+    }
+}
+
+impl IncreasingRate<NormalizedTimeMetric> for IncreasingKeyframeRate {}
+
+impl IncreasingRate<DenormalizedTimeMetric> for IncreasingKeyframeRate {}
+
+#[cfg(test)]
+mod keyframes_tests {
+    use super::*;
+
+    #[test]
+    fn test_keyframe_rate() {
+        let keyframes = vec![(0.0, 0.0), (0.2, 0.0), (0.6, 0.9), (1.0, 1.0)];
+        let linear = KeyframeRate::new(KeyframeInterpolation::Linear, keyframes.clone()).unwrap();
+        assert!((linear.eval_time(0.4) - 0.45).abs() < 1e-6);
+        assert_eq!(linear.eval_time(-1.0), 0.0);
+        assert_eq!(linear.eval_time(2.0), 1.0);
+
+        let hermite = KeyframeRate::new(KeyframeInterpolation::Hermite, keyframes.clone()).unwrap();
+        assert_eq!(hermite.eval_time(0.6), 0.9);
+        assert!(hermite.increasing().is_ok());
+
+        // The Catmull-Rom tangent at `(0.2, 0.0)` is positive, dipping the curve below zero before it.
+        let catmull_rom = KeyframeRate::new(KeyframeInterpolation::CatmullRom, keyframes).unwrap();
+        assert!(catmull_rom.eval_time(0.15) < 0.0);
+        assert!(matches!(
+            catmull_rom.increasing(),
+            Err(KeyframeRateError::NotIncreasing { index: 0 })
+        ));
+
+        // The Catmull-Rom tangent at `(1.0, 1.0)` overshoots the last keyframe, and is clamped.
+        let keyframes = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 1.1)];
+        let catmull_rom = KeyframeRate::new(KeyframeInterpolation::CatmullRom, keyframes).unwrap();
+        let samples = |rate: &KeyframeRate| {
+            (0..=200)
+                .map(|step| rate.eval_time(step as f32 / 100.0))
+                .collect::<Vec<_>>()
+        };
+        assert!(
+            samples(&catmull_rom)
+                .windows(2)
+                .any(|window| window[1] < window[0])
+        );
+        let IncreasingKeyframeRate(clamped) = catmull_rom.increasing().unwrap();
+        assert!(
+            samples(&clamped)
+                .windows(2)
+                .all(|window| window[1] >= window[0])
+        );
+        assert_eq!(clamped.eval_time(1.0), 1.0);
+        assert_eq!(clamped.eval_time(2.0), 1.1);
+
+        // Tangents outside the circle of radius 3, yet within the monotonicity region, are kept.
+        let keyframes = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)];
+        let mut rate = KeyframeRate::new(KeyframeInterpolation::CatmullRom, keyframes).unwrap();
+        rate.tangents = vec![2.5, 2.5, 2.5, 2.5];
+        assert_eq!(rate.increasing().unwrap().0.tangents, [2.5, 2.5, 2.5, 2.5]);
+
+        assert!(matches!(
+            KeyframeRate::new(KeyframeInterpolation::Linear, vec![(0.0, 0.0), (0.0, 1.0)]),
+            Err(KeyframeRateError::NotSorted { index: 1 })
+        ));
+    }
+}
@@ -1 +1,2 @@
+pub mod keyframes;
 pub mod rates;
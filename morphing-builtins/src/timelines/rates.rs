@@ -1,13 +1,18 @@
 use std::f32::consts::FRAC_PI_2;
 
-use morphing_macros::rate;
+use morphing_core::rate;
 
-#[rate(normalized, denormalized, increasing)]
+#[rate(normalized, denormalized, increasing, inverse = "t.max(0.0)")]
 pub fn identity(t: f32) -> f32 {
     t
 }
 
-#[rate(denormalized, increasing, assert = "speed.is_sign_positive()")]
+#[rate(
+    denormalized,
+    increasing,
+    assert = "speed > 0.0",
+    inverse = "(t / speed).max(0.0)"
+)]
 pub fn speed(t: f32, speed: f32) -> f32 {
     t * speed
 }
@@ -24,37 +29,45 @@ pub fn smoother(t: f32) -> f32 {
 
 // From https://docs.rs/interpolation/latest/src/interpolation/ease.rs.html
 
-#[rate(normalized, increasing)]
+#[rate(normalized, increasing, inverse = "t.max(0.0).sqrt()")]
 pub fn quadratic(t: f32) -> f32 {
     t * t
 }
 
-#[rate(normalized, increasing)]
+#[rate(normalized, increasing, inverse = "t.max(0.0).cbrt()")]
 pub fn cubic(t: f32) -> f32 {
     t * t * t
 }
 
-#[rate(normalized, increasing)]
+#[rate(normalized, increasing, inverse = "t.max(0.0).powf(0.25)")]
 pub fn quartic(t: f32) -> f32 {
     t * t * t * t
 }
 
-#[rate(normalized, increasing)]
+#[rate(normalized, increasing, inverse = "t.max(0.0).powf(0.2)")]
 pub fn quintic(t: f32) -> f32 {
     t * t * t * t * t
 }
 
-#[rate(normalized, increasing)]
+#[rate(
+    normalized,
+    increasing,
+    inverse = "1.0 - (1.0 - t.clamp(0.0, 1.0)).asin() / FRAC_PI_2"
+)]
 pub fn sine(t: f32) -> f32 {
     1.0 - (FRAC_PI_2 * (1.0 - t)).sin()
 }
 
-#[rate(normalized, increasing)]
+#[rate(
+    normalized,
+    increasing,
+    inverse = "(t.clamp(0.0, 1.0) * (2.0 - t.clamp(0.0, 1.0))).sqrt()"
+)]
 pub fn circular(t: f32) -> f32 {
     1.0 - (1.0 - t * t).sqrt()
 }
 
-#[rate(normalized, increasing)]
+#[rate(normalized, increasing, inverse = "(1.0 + 0.1 * t.log2()).max(0.0)")]
 pub fn exponential(t: f32) -> f32 {
     2.0f32.powf(-10.0 * (1.0 - t))
 }
@@ -69,6 +82,82 @@ pub fn back(t: f32) -> f32 {
     t * t * t - t * (std::f32::consts::PI * t).sin()
 }
 
+// CSS `cubic-bezier(x1, y1, x2, y2)`. The curve parameter is solved from `t`
+// by Newton's method, falling back to bisection when the slope vanishes.
+#[rate(
+    normalized,
+    assert = "(0.0..=1.0).contains(&x1) && (0.0..=1.0).contains(&x2)"
+)]
+pub fn cubic_bezier(t: f32, x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
+    fn bezier(s: f32, p1: f32, p2: f32) -> f32 {
+        3.0 * (1.0 - s) * (1.0 - s) * s * p1 + 3.0 * (1.0 - s) * s * s * p2 + s * s * s
+    }
+
+    fn bezier_derivative(s: f32, p1: f32, p2: f32) -> f32 {
+        3.0 * (1.0 - s) * (1.0 - s) * p1
+            + 6.0 * (1.0 - s) * s * (p2 - p1)
+            + 3.0 * s * s * (1.0 - p2)
+    }
+
+    const EPSILON: f32 = 1e-6;
+    let mut s = t;
+    for _ in 0..8 {
+        let error = bezier(s, x1, x2) - t;
+        if error.abs() < EPSILON {
+            return bezier(s, y1, y2);
+        }
+        let slope = bezier_derivative(s, x1, x2);
+        if slope.abs() < EPSILON {
+            break;
+        }
+        s -= error / slope;
+    }
+    let (mut low, mut high) = (0.0, 1.0);
+    s = t;
+    while high - low > EPSILON {
+        if bezier(s, x1, x2) < t {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = 0.5 * (low + high);
+    }
+    bezier(s, y1, y2)
+}
+
+// CSS `steps(steps, jump-end)`.
+#[rate(normalized, increasing, assert = "steps > 0")]
+pub fn steps(t: f32, steps: u32) -> f32 {
+    if t >= 1.0 {
+        1.0
+    } else {
+        (t * steps as f32).floor() / steps as f32
+    }
+}
+
+// Damped spring released from `0` towards `1`, taking the duration as the unit of time.
+// The spring is not guaranteed to settle at `t = 1`; chain with `clamp` to cut off overshoot.
+#[rate(
+    normalized,
+    assert = "stiffness > 0.0 && damping >= 0.0 && mass > 0.0"
+)]
+pub fn spring(t: f32, stiffness: f32, damping: f32, mass: f32) -> f32 {
+    let omega = (stiffness / mass).sqrt();
+    let zeta = damping / (2.0 * (stiffness * mass).sqrt());
+    if (zeta - 1.0).abs() < 1e-3 {
+        1.0 - (-omega * t).exp() * (1.0 + omega * t)
+    } else if zeta < 1.0 {
+        let omega_damped = omega * (1.0 - zeta * zeta).sqrt();
+        1.0 - (-zeta * omega * t).exp()
+            * ((omega_damped * t).cos() + zeta * omega / omega_damped * (omega_damped * t).sin())
+    } else {
+        let discriminant = (zeta * zeta - 1.0).sqrt();
+        let r1 = -omega * (zeta - discriminant);
+        let r2 = -omega * (zeta + discriminant);
+        1.0 - (r2 * (r1 * t).exp() - r1 * (r2 * t).exp()) / (r2 - r1)
+    }
+}
+
 macro_rules! rate_family {
     ($name:ident => $name_in:ident, $name_out:ident, $name_in_out:ident) => {
         #[rate(normalized, increasing)]
@@ -99,3 +188,57 @@ rate_family!(quintic => quintic_in, quintic_out, quintic_in_out);
 rate_family!(sine => sine_in, sine_out, sine_in_out);
 rate_family!(circular => circular_in, circular_out, circular_in_out);
 rate_family!(exponential => exponential_in, exponential_out, exponential_in_out);
+
+#[cfg(test)]
+mod rates_tests {
+    use super::*;
+
+    #[test]
+    fn test_parameterized_rates() {
+        // `ease-in-out` is symmetric about the center.
+        assert!((cubic_bezier(0.5, 0.42, 0.0, 0.58, 1.0) - 0.5).abs() < 1e-5);
+        assert!(
+            (cubic_bezier(0.3, 0.42, 0.0, 0.58, 1.0) + cubic_bezier(0.7, 0.42, 0.0, 0.58, 1.0)
+                - 1.0)
+                .abs()
+                < 1e-5
+        );
+        assert!((cubic_bezier(0.3, 1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0) - 0.3).abs() < 1e-5);
+        assert_eq!(steps(0.49, 4), 0.25);
+        assert_eq!(steps(1.0, 4), 1.0);
+        assert_eq!(spring(0.0, 100.0, 10.0, 1.0), 0.0);
+        assert!((spring(1.0, 400.0, 40.0, 1.0) - 1.0).abs() < 1e-3);
+        assert!(spring(0.2, 400.0, 4.0, 1.0) > 1.0);
+    }
+
+    #[test]
+    fn test_closed_form_inverses() {
+        use morphing_core::IncreasingRate;
+        use morphing_core::NormalizedTimeMetric;
+
+        fn assert_inverse<R>(rate: R, f: fn(f32) -> f32)
+        where
+            R: IncreasingRate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric>,
+        {
+            for t in [0.1, 0.25, 0.5, 0.9] {
+                assert!((*rate.inverse(f(t).into()) - t).abs() < 1e-4);
+            }
+        }
+
+        assert_inverse(__quadratic_Rate {}, quadratic);
+        assert_inverse(__quintic_Rate {}, quintic);
+        assert_inverse(__sine_Rate {}, sine);
+        assert_inverse(__circular_Rate {}, circular);
+        assert_inverse(__exponential_Rate {}, exponential);
+        assert_inverse(__smooth_Rate {}, smooth);
+    }
+
+    #[test]
+    #[should_panic(expected = "speed > 0.0")]
+    fn test_zero_speed() {
+        use morphing_core::DenormalizedTimeMetric;
+        use morphing_core::IdentityRate;
+
+        __speed_Trait::<DenormalizedTimeMetric>::speed(IdentityRate, 0.0);
+    }
+}
@@ -0,0 +1,264 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::process::Stdio;
+
+use clap::Parser;
+use morphing_builtins::configs::general::Fps;
+use morphing_builtins::configs::general::GeneralConfig;
+use morphing_builtins::configs::general::Resolution;
+use morphing_core::ChapterSymbol;
+use morphing_core::RendererError;
+use morphing_core::Time;
+use morphing_core::Timeline;
+use morphing_core::TimelineResources;
+use morphing_core::build_chapter;
+use morphing_core::call_entrypoint;
+
+#[derive(clap::Parser)]
+#[command(
+    name = "morphing",
+    about = "Lists and renders the scenes of a chapter without a window"
+)]
+struct Cli {
+    #[arg(
+        long,
+        default_value = ".",
+        help = "A chapter crate to build with cargo, or an already built chapter library"
+    )]
+    chapter: PathBuf,
+    #[arg(
+        long = "set",
+        value_name = "KEY=VALUE",
+        help = "Overrides a config value, e.g. `--set general.background_color=#000000`"
+    )]
+    overrides: Vec<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    #[command(about = "Prints the name of every scene")]
+    List,
+    #[command(about = "Renders a scene into numbered PNG frames or a video")]
+    Render {
+        scene: String,
+        #[arg(long, help = "Directory to write into")]
+        out: PathBuf,
+        #[arg(long, value_enum, default_value_t = Format::Png)]
+        format: Format,
+        #[arg(long, default_value_t = 0.0, help = "Start time in seconds")]
+        from: Time,
+        #[arg(long, help = "End time in seconds [default: the scene duration]")]
+        to: Option<Time>,
+        #[arg(long, help = "Shorthand for `--set general.fps=<FPS>`")]
+        fps: Option<Fps>,
+        #[arg(long, help = "Shorthand for `--set general.resolution=<RESOLUTION>`")]
+        resolution: Option<Resolution>,
+    },
+    #[command(about = "Renders a single frame of a scene into a PNG")]
+    Still {
+        scene: String,
+        #[arg(long, help = "Time in seconds")]
+        time: Time,
+        out: PathBuf,
+        #[arg(long, help = "Shorthand for `--set general.resolution=<RESOLUTION>`")]
+        resolution: Option<Resolution>,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Png,
+    // Encoded as H.264 by `ffmpeg`, which has to be on `PATH`.
+    Video,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let Cli {
+        chapter,
+        mut overrides,
+        command,
+    } = cli;
+    let chapter_symbol = load_chapter(&chapter)?;
+    match command {
+        Command::List => {
+            let mut names: Vec<&String> = chapter_symbol.scenes().keys().collect();
+            names.sort();
+            for name in names {
+                match chapter_symbol.scenes()[name].config_error() {
+                    Some(error) => println!("{name} ({error})"),
+                    None => println!("{name}"),
+                }
+            }
+        }
+        Command::Render {
+            scene,
+            out,
+            format,
+            from,
+            to,
+            fps,
+            resolution,
+        } => {
+            overrides.extend(fps.map(|fps| format!("general.fps={fps}")));
+            overrides
+                .extend(resolution.map(|resolution| format!("general.resolution={resolution}")));
+            let (general_config, timeline) = load_scene(&chapter_symbol, &scene, &overrides)?;
+            let renderer = general_config.renderer()?;
+            let fps = general_config.fps;
+            let to = to.unwrap_or(timeline.duration());
+            let frame_times = (0..fps.frame_count((to - from).max(0.0) as f64))
+                .map(|frame| from + fps.frame_time(frame) as Time);
+            let frames = frame_times.map({
+                let mut resources = TimelineResources::default();
+                move |time| renderer.render(&timeline, &mut resources, time)
+            });
+            std::fs::create_dir_all(&out)?;
+            let path = match format {
+                Format::Png => {
+                    let dir = out.join(file_name(&scene));
+                    std::fs::create_dir_all(&dir)?;
+                    for (index, pixels) in frames.enumerate() {
+                        write_png(
+                            &dir.join(format!("{index:05}.png")),
+                            general_config.resolution,
+                            &pixels?,
+                        )?;
+                    }
+                    dir
+                }
+                Format::Video => {
+                    let path = out.join(format!("{}.mp4", file_name(&scene)));
+                    write_video(&path, general_config.resolution, fps, frames)?;
+                    path
+                }
+            };
+            println!("{}", path.display());
+        }
+        Command::Still {
+            scene,
+            time,
+            out,
+            resolution,
+        } => {
+            overrides
+                .extend(resolution.map(|resolution| format!("general.resolution={resolution}")));
+            let (general_config, timeline) = load_scene(&chapter_symbol, &scene, &overrides)?;
+            let renderer = general_config.renderer()?;
+            let pixels = renderer.render(&timeline, &mut TimelineResources::default(), time)?;
+            write_png(&out, general_config.resolution, &pixels)?;
+            println!("{}", out.display());
+        }
+    }
+    Ok(())
+}
+
+// A directory is taken as a chapter crate and built first. The config schema is
+// written next to the library, as the GUI does.
+fn load_chapter(path: &Path) -> Result<ChapterSymbol, Box<dyn Error>> {
+    let library_path = if path.is_dir() {
+        build_chapter(path)?
+    } else {
+        path.to_path_buf()
+    };
+    let chapter_symbol = call_entrypoint(&library_path)?;
+    if let Some(dir) = library_path.parent()
+        && let Err(error) = chapter_symbol.emit_schema(dir)
+    {
+        eprintln!("Failed to write config schema: {error}");
+    }
+    Ok(chapter_symbol)
+}
+
+// The `[general]` table is read from the layers of the scene, so that scene configs may set the resolution.
+fn load_scene(
+    chapter_symbol: &ChapterSymbol,
+    scene: &str,
+    overrides: &[String],
+) -> Result<(GeneralConfig, Timeline), Box<dyn Error>> {
+    let entry = chapter_symbol
+        .scenes()
+        .get(scene)
+        .ok_or_else(|| format!("No scene named `{scene}`, see `morphing list`"))?;
+    let layers = chapter_symbol.config_layers().with_overrides(overrides)?;
+    let general_config = GeneralConfig::from_layers(&entry.config_layers(layers.clone()))?;
+    let timeline = entry.timeline(layers)?;
+    Ok((general_config, timeline))
+}
+
+// Scene names may hold `::` and variant brackets, which are not portable in paths.
+fn file_name(scene: &str) -> String {
+    scene
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn write_png(path: &Path, resolution: Resolution, pixels: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        resolution.width,
+        resolution.height,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(())
+}
+
+// Raw frames are piped into `ffmpeg`. Odd sizes are padded, as `yuv420p` needs even ones.
+fn write_video(
+    path: &Path,
+    resolution: Resolution,
+    fps: Fps,
+    frames: impl Iterator<Item = Result<Vec<u8>, RendererError>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut ffmpeg = std::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error"])
+        .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+        .args([
+            "-s",
+            &resolution.to_string(),
+            "-framerate",
+            &fps.to_string(),
+        ])
+        .args(["-i", "-"])
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+        .args(["-c:v", "libx264", "-pix_fmt", "yuv420p"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("Failed to run ffmpeg: {error}"))?;
+    let mut stdin = ffmpeg.stdin.take().unwrap();
+    for pixels in frames {
+        stdin.write_all(&pixels?)?;
+    }
+    drop(stdin);
+    let status = ffmpeg.wait()?;
+    if !status.success() {
+        return Err(format!("ffmpeg exited with {status}").into());
+    }
+    Ok(())
+}
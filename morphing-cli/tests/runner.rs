@@ -0,0 +1,79 @@
+// Runs the chapter in `tests/smoke_chapter` through `run_isolated`, with this binary as the runner.
+// Without the libtest harness, since workers are spawned from `main` with runner arguments.
+use std::path::Path;
+
+use morphing_core::Failure;
+use morphing_core::LineOutput;
+use morphing_core::RedirectedOutput;
+use morphing_core::SceneData;
+use morphing_core::build_chapter;
+use morphing_core::read_and_deserialize;
+use morphing_core::run_isolated;
+
+fn main() {
+    morphing_core::runner_mode();
+
+    // SAFETY: No other thread is running yet.
+    unsafe {
+        // Shares the target dir with the workspace, as in `tests/smoke.rs`.
+        std::env::set_var(
+            "CARGO_TARGET_DIR",
+            Path::new(env!("CARGO_TARGET_TMPDIR")).parent().unwrap(),
+        );
+    }
+    let library =
+        build_chapter(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/smoke_chapter")).unwrap();
+    // Records are read back the way the GUI reads them from a runner process.
+    let line_outputs: Vec<LineOutput> = run_isolated(&library)
+        .map(|line_output| {
+            read_and_deserialize(&serde_json::to_string(&line_output).unwrap()).unwrap()
+        })
+        .collect();
+
+    let [
+        LineOutput::Project(project),
+        LineOutput::Scene(aborting_name, aborting),
+        LineOutput::Scene(printing_name, printing),
+        LineOutput::Scene(square_name, square),
+    ] = &line_outputs[..]
+    else {
+        panic!("Unexpected records: {line_outputs:?}");
+    };
+    assert!(matches!(
+        project,
+        RedirectedOutput { result: Ok(names), .. } if names == &[
+            "smoke_chapter::aborting",
+            "smoke_chapter::printing",
+            "smoke_chapter::square",
+        ]
+    ));
+
+    assert_eq!(aborting_name, "smoke_chapter::aborting");
+    assert!(
+        matches!(aborting.result, Err(Failure::Crash { .. })),
+        "{aborting:?}"
+    );
+
+    assert_eq!(printing_name, "smoke_chapter::printing");
+    assert_eq!(
+        printing.stdout_lines,
+        [r#"{"Err":{"error":{"message":"printed"}}}"#]
+    );
+    assert!(
+        printing
+            .stderr_lines
+            .iter()
+            .any(|line| line == "printed to stderr")
+    );
+    assert!(
+        matches!(printing.result, Ok(SceneData { duration }) if duration == 2.0),
+        "{printing:?}"
+    );
+
+    assert_eq!(square_name, "smoke_chapter::square");
+    assert!(
+        matches!(square.result, Ok(SceneData { duration }) if duration == 1.0),
+        "{square:?}"
+    );
+    println!("runner: ok");
+}
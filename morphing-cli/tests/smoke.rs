@@ -0,0 +1,76 @@
+// Runs the binary against the chapter in `tests/smoke_chapter`, which it builds with cargo first.
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+
+fn morphing(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_morphing"))
+        .arg("--chapter")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/smoke_chapter"))
+        .args(args)
+        // Cargo releases the lock on its target dir before running tests,
+        // so the chapter is built next to the workspace, reusing its dependencies.
+        .env("CARGO_TARGET_DIR", target_dir())
+        .output()
+        .unwrap()
+}
+
+fn target_dir() -> &'static Path {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).parent().unwrap()
+}
+
+fn tmp_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+#[test]
+fn test_list_and_still() {
+    let list = morphing(&["list"]);
+    assert!(
+        list.status.success(),
+        "{}",
+        String::from_utf8_lossy(&list.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&list.stdout),
+        "smoke_chapter::aborting\nsmoke_chapter::printing\nsmoke_chapter::square\n"
+    );
+
+    let out = tmp_path("square.png");
+    let still = morphing(&[
+        "still",
+        "smoke_chapter::square",
+        "--time",
+        "0.5",
+        "--resolution",
+        "64x36",
+        out.to_str().unwrap(),
+    ]);
+    let stderr = String::from_utf8_lossy(&still.stderr);
+    // Without any adapter, not even a software one, the scene is only run up to the renderer.
+    // The skip is written past the output capture, so that it shows up even though the test passes.
+    if let Some(error) = stderr
+        .lines()
+        .find(|line| line.contains("Failed to request adapter"))
+    {
+        writeln!(
+            std::io::stderr(),
+            "\nSKIPPED the still of `test_list_and_still`: {error}"
+        )
+        .unwrap();
+        return;
+    }
+    assert!(still.status.success(), "{stderr}");
+
+    let mut reader = png::Decoder::new(std::io::BufReader::new(std::fs::File::open(&out).unwrap()))
+        .read_info()
+        .unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (64, 36));
+    // The square covers the center, while the corner shows the background.
+    let pixel = |x: usize, y: usize| &pixels[(y * 64 + x) * 4..][..4];
+    assert_ne!(pixel(32, 18), pixel(0, 0));
+}
@@ -0,0 +1,33 @@
+// Built and loaded by `tests/smoke.rs` and `tests/runner.rs`.
+extern crate morphing_core as morphing;
+
+use morphing_builtins::mobjects::shape::Rect;
+use morphing_core::Supervisor;
+use morphing_core::chapter;
+use morphing_core::scene;
+
+#[derive(serde::Deserialize)]
+struct EmptyConfig {}
+
+#[scene]
+fn square(sv: &mut Supervisor<EmptyConfig>) {
+    let square = sv.spawn(Rect(nalgebra::Vector2::new(2.0, 2.0)));
+    sv.wait(1.0);
+    drop(square);
+}
+
+// Prints a line that would pass for a report, had stdout been used for reporting.
+#[scene]
+fn printing(sv: &mut Supervisor<EmptyConfig>) {
+    println!(r#"{{"Err":{{"error":{{"message":"printed"}}}}}}"#);
+    eprintln!("printed to stderr");
+    sv.wait(2.0);
+}
+
+#[scene]
+fn aborting(_sv: &mut Supervisor<EmptyConfig>) {
+    std::process::abort();
+}
+
+#[chapter]
+extern crate self;
@@ -0,0 +1,11 @@
+// Records the compiler version, which is checked when loading chapter libraries.
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let output = std::process::Command::new(rustc)
+        .arg("--version")
+        .output()
+        .expect("Failed to run rustc");
+    let version = String::from_utf8(output.stdout).expect("Non UTF-8 rustc version");
+    println!("cargo:rustc-env=MORPHING_RUSTC_VERSION={}", version.trim());
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
@@ -0,0 +1,79 @@
+use std::io::BufRead;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+
+#[derive(Debug)]
+pub enum ChapterBuildError {
+    Io(std::io::Error),
+    // Diagnostics are rendered to the inherited stderr.
+    Failed(std::process::ExitStatus),
+    NoLibrary(PathBuf),
+}
+
+impl std::fmt::Display for ChapterBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Failed to run cargo: {error}"),
+            Self::Failed(status) => write!(f, "Chapter build failed ({status})"),
+            Self::NoLibrary(manifest_path) => write!(
+                f,
+                "No dynamic library built from {}, expecting `crate-type = [\"dylib\"]`",
+                manifest_path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChapterBuildError {}
+
+impl From<std::io::Error> for ChapterBuildError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+// Builds the chapter crate at `crate_dir` and returns the path of its dynamic library,
+// as reported by cargo's JSON messages.
+pub fn build_chapter(crate_dir: &Path) -> Result<PathBuf, ChapterBuildError> {
+    let manifest_path = crate_dir.join("Cargo.toml").canonicalize()?;
+    let mut child = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+        .arg("build")
+        .arg("--message-format=json-render-diagnostics")
+        .arg("--manifest-path")
+        .arg(&manifest_path)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut library_path = None;
+    for line in std::io::BufReader::new(child.stdout.take().unwrap()).lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(&line?) else {
+            continue;
+        };
+        if message["reason"] != "compiler-artifact"
+            || message["manifest_path"]
+                .as_str()
+                .is_none_or(|path| Path::new(path) != manifest_path)
+        {
+            continue;
+        }
+        if let Some(path) = message["filenames"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(serde_json::Value::as_str)
+            .map(PathBuf::from)
+            .find(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == std::env::consts::DLL_EXTENSION)
+            })
+        {
+            library_path = Some(path);
+        }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(ChapterBuildError::Failed(status));
+    }
+    library_path.ok_or(ChapterBuildError::NoLibrary(manifest_path))
+}
@@ -0,0 +1,109 @@
+pub trait Component {
+    type ShaderTypes: ComponentShaderTypes;
+
+    fn to_shader_types(&self) -> Self::ShaderTypes;
+}
+
+// Implemented by `wgpu_shader_types!`.
+pub trait ComponentShaderTypes {
+    type Buffers;
+
+    // Rust-side layouts of the bindings, checked against WGSL sources by `include_wgsl!`.
+    const BINDINGS: &'static [WgslBinding];
+
+    // WGSL struct declarations used by the bindings, followed by the bindings themselves
+    // placed in `@group(group)`.
+    fn wgsl_declarations(group: u32) -> String;
+    fn bind_group_layout(device: &wgpu::Device) -> &'static wgpu::BindGroupLayout;
+    fn bind_group_from_buffers(device: &wgpu::Device, buffers: &Self::Buffers) -> wgpu::BindGroup;
+    fn new_buffers(&self, device: &wgpu::Device) -> Self::Buffers;
+    fn new_buffers_initialized(&self, device: &wgpu::Device) -> Self::Buffers;
+    fn write_buffers(&self, queue: &wgpu::Queue, buffers: &mut Self::Buffers);
+}
+
+// Implemented by `wgpu_struct!`.
+pub trait WgslStruct {
+    const WGSL_DECLARATION: &'static str;
+    const MEMBERS: &'static [WgslMember];
+}
+
+pub struct WgslBinding {
+    pub binding: u32,
+    pub address_space: &'static str,
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub size: u64,
+    // Members of the struct type, or of the element type for arrays.
+    pub members: &'static [WgslMember],
+}
+
+pub struct WgslMember {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub offset: u64,
+    // Members of the struct type, or of the element type for arrays.
+    pub members: &'static [WgslMember],
+}
+
+// The comparisons below run in const context, where `include_wgsl!` turns
+// every mismatch into a compilation error.
+impl WgslBinding {
+    pub const fn find(bindings: &'static [Self], binding: u32) -> Option<&'static Self> {
+        let mut index = 0;
+        while index < bindings.len() {
+            if bindings[index].binding == binding {
+                return Some(&bindings[index]);
+            }
+            index += 1;
+        }
+        None
+    }
+
+    pub const fn matches(&self, address_space: &str, name: &str, ty: &str, size: u64) -> bool {
+        str_eq(self.address_space, address_space)
+            && str_eq(self.name, name)
+            && str_eq(self.ty, ty)
+            && self.size == size
+    }
+    // The first of `bindings` whose index is not among `wgsl_bindings`.
+    pub const fn find_missing(
+        bindings: &'static [Self],
+        wgsl_bindings: &[u32],
+    ) -> Option<&'static Self> {
+        let mut index = 0;
+        while index < bindings.len() {
+            let mut wgsl_index = 0;
+            while wgsl_index < wgsl_bindings.len()
+                && wgsl_bindings[wgsl_index] != bindings[index].binding
+            {
+                wgsl_index += 1;
+            }
+            if wgsl_index == wgsl_bindings.len() {
+                return Some(&bindings[index]);
+            }
+            index += 1;
+        }
+        None
+    }
+}
+
+impl WgslMember {
+    pub const fn matches(&self, name: &str, ty: &str, offset: u64) -> bool {
+        str_eq(self.name, name) && str_eq(self.ty, ty) && self.offset == offset
+    }
+}
+
+const fn str_eq(lhs: &str, rhs: &str) -> bool {
+    let (lhs, rhs) = (lhs.as_bytes(), rhs.as_bytes());
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+    let mut index = 0;
+    while index < lhs.len() {
+        if lhs[index] != rhs[index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
@@ -0,0 +1,396 @@
+use config::Source;
+
+pub type ConfigFile = config::File<config::FileSourceString, config::FileFormat>;
+
+// Default configs shipped by crates, e.g. `configs/general.toml` of the builtins.
+pub struct ConfigFallbackContent(pub &'static str);
+
+inventory::collect!(ConfigFallbackContent);
+
+// Layers in increasing priority. Values in later layers override those in earlier ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigLayer {
+    Fallback,
+    Chapter,
+    Scene,
+    Variant,
+    Environment,
+    CommandLine,
+}
+
+impl std::fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fallback => write!(f, "crate fallback config"),
+            Self::Chapter => write!(f, "chapter config"),
+            Self::Scene => write!(f, "scene config"),
+            Self::Variant => write!(f, "scene variant config"),
+            Self::Environment => write!(f, "`MORPHING_*` environment variables"),
+            Self::CommandLine => write!(f, "command line overrides"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    // A layer failed to parse.
+    Layer {
+        layer: ConfigLayer,
+        error: Box<config::ConfigError>,
+    },
+    // A value failed to deserialize into the scene config type.
+    // `layer` is the layer the offending value came from, if any defines it.
+    Value {
+        key: String,
+        layer: Option<ConfigLayer>,
+        expected: String,
+        error: Box<config::ConfigError>,
+    },
+    Other(Box<config::ConfigError>),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Layer { layer, error } => write!(f, "Failed to load {layer}: {error}"),
+            Self::Value {
+                key,
+                layer: Some(layer),
+                expected,
+                error,
+            } => write!(
+                f,
+                "Invalid config value `{key}` from {layer}, expected {expected}: {error}"
+            ),
+            Self::Value {
+                key,
+                layer: None,
+                expected,
+                error,
+            } => write!(
+                f,
+                "Missing config value `{key}`, expected {expected}: {error}"
+            ),
+            Self::Other(error) => write!(f, "Failed to deserialize config: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Layer { error, .. } | Self::Value { error, .. } | Self::Other(error) => {
+                Some(error.as_ref())
+            }
+        }
+    }
+}
+
+// Config sources tagged with their layers, deserialized into the config type of a scene.
+#[derive(Debug)]
+pub struct ConfigLayers {
+    layers: Vec<(ConfigLayer, Box<dyn Source + Send + Sync>)>,
+}
+
+impl Clone for ConfigLayers {
+    fn clone(&self) -> Self {
+        Self {
+            layers: self
+                .layers
+                .iter()
+                .map(|(layer, source)| (*layer, source.clone_into_box()))
+                .collect(),
+        }
+    }
+}
+
+impl Default for ConfigLayers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigLayers {
+    // Starts with the registered fallbacks and the environment, e.g.
+    // `MORPHING_GENERAL__FPS=30` overrides `general.fps`.
+    pub fn new() -> Self {
+        inventory::iter::<ConfigFallbackContent>
+            .into_iter()
+            .fold(Self { layers: Vec::new() }, |layers, fallback| {
+                layers.with(
+                    ConfigLayer::Fallback,
+                    ConfigFile::from_str(fallback.0, config::FileFormat::Toml),
+                )
+            })
+            .with(
+                ConfigLayer::Environment,
+                config::Environment::with_prefix("MORPHING")
+                    .prefix_separator("_")
+                    .separator("__")
+                    .try_parsing(true),
+            )
+    }
+
+    pub fn with<S>(mut self, layer: ConfigLayer, source: S) -> Self
+    where
+        S: 'static + Source + Send + Sync,
+    {
+        // Stable, so sources within the same layer keep their order.
+        self.layers.push((layer, Box::new(source)));
+        self.layers.sort_by_key(|(layer, _)| *layer);
+        self
+    }
+
+    // Overrides given as `key=value`, with dotted keys, e.g. `general.fps=30`.
+    pub fn with_overrides<I, S>(self, overrides: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let builder =
+            overrides
+                .into_iter()
+                .try_fold(config::Config::builder(), |builder, assignment| {
+                    let assignment = assignment.as_ref();
+                    let (key, value) =
+                        assignment
+                            .split_once('=')
+                            .ok_or_else(|| ConfigError::Layer {
+                                layer: ConfigLayer::CommandLine,
+                                error: Box::new(config::ConfigError::Message(format!(
+                                    "Expected `key=value`, found `{assignment}`"
+                                ))),
+                            })?;
+                    builder
+                        .set_override(key.trim(), value.trim())
+                        .map_err(|error| ConfigError::Layer {
+                            layer: ConfigLayer::CommandLine,
+                            error: Box::new(error),
+                        })
+                })?;
+        let overrides = builder.build().map_err(|error| ConfigError::Layer {
+            layer: ConfigLayer::CommandLine,
+            error: Box::new(error),
+        })?;
+        Ok(self.with(ConfigLayer::CommandLine, overrides))
+    }
+
+    pub fn deserialize<C>(&self) -> Result<C, ConfigError>
+    where
+        C: serde::de::DeserializeOwned,
+    {
+        let maps = self
+            .layers
+            .iter()
+            .map(|(layer, source)| {
+                source
+                    .collect()
+                    .map(|map| (*layer, map))
+                    .map_err(|error| ConfigError::Layer {
+                        layer: *layer,
+                        error: Box::new(error),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let config = config::Config::builder()
+            .add_source(
+                self.layers
+                    .iter()
+                    .map(|(_, source)| source.clone_into_box())
+                    .collect::<Vec<_>>(),
+            )
+            .build()
+            .map_err(|error| ConfigError::Other(Box::new(error)))?;
+        config.try_deserialize().map_err(|error| match error {
+            config::ConfigError::Type {
+                key: Some(ref key),
+                expected,
+                ..
+            } => ConfigError::Value {
+                key: key.clone(),
+                layer: Self::find_layer(&maps, key),
+                expected: expected.into(),
+                error: Box::new(error),
+            },
+            // Custom errors of `Deserialize` impls, e.g. a malformed resolution.
+            config::ConfigError::At {
+                key: Some(key),
+                error,
+                ..
+            } => ConfigError::Value {
+                layer: Self::find_layer(&maps, &key),
+                key,
+                expected: "a valid value".into(),
+                error,
+            },
+            // Attributed to the layer defining the enclosing table, if any.
+            config::ConfigError::NotFound(ref key) => ConfigError::Value {
+                key: key.clone(),
+                layer: key
+                    .rsplit_once('.')
+                    .and_then(|(table, _)| Self::find_layer(&maps, table)),
+                expected: "a value".into(),
+                error: Box::new(error),
+            },
+            error => ConfigError::Other(Box::new(error)),
+        })
+    }
+
+    // The last layer defining `key` is the one that provided the value.
+    fn find_layer(
+        maps: &[(ConfigLayer, config::Map<String, config::Value>)],
+        key: &str,
+    ) -> Option<ConfigLayer> {
+        maps.iter()
+            .rev()
+            .find(|(_, map)| lookup(map, key))
+            .map(|(layer, _)| *layer)
+    }
+}
+
+// Keys are dotted paths, possibly with array subscripts, e.g. `typst.font_paths[0]`.
+fn lookup(map: &config::Map<String, config::Value>, key: &str) -> bool {
+    let segments: Vec<_> = key
+        .split('.')
+        .map(|segment| segment.split_once('[').map_or(segment, |(name, _)| name))
+        .collect();
+    lookup_segments(map, &segments)
+}
+
+fn lookup_segments(map: &config::Map<String, config::Value>, segments: &[&str]) -> bool {
+    match segments {
+        [] => false,
+        [segment, rest @ ..] => match map.get(*segment) {
+            None => false,
+            Some(_) if rest.is_empty() => true,
+            Some(value) => value
+                .clone()
+                .into_table()
+                .map_or(true, |table| lookup_segments(&table, rest)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod config_layers_tests {
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct GeneralConfig {
+        fps: f32,
+        resolution: String,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct SceneConfig {
+        general: GeneralConfig,
+    }
+
+    fn toml(content: &'static str) -> ConfigFile {
+        ConfigFile::from_str(content, config::FileFormat::Toml)
+    }
+
+    #[test]
+    fn test_config_layers() {
+        let layers = ConfigLayers { layers: Vec::new() }
+            .with(ConfigLayer::Scene, toml("general.fps = 30.0"))
+            .with(
+                ConfigLayer::Fallback,
+                toml("[general]\nfps = 60.0\nresolution = \"1920x1080\""),
+            );
+        let config: SceneConfig = layers.clone().deserialize().unwrap();
+        assert_eq!(config.general.fps, 30.0);
+        assert_eq!(config.general.resolution, "1920x1080");
+
+        let config: SceneConfig = layers
+            .clone()
+            .with_overrides(["general.fps = 24"])
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert_eq!(config.general.fps, 24.0);
+
+        let error = layers
+            .with(
+                ConfigLayer::Chapter,
+                toml("general.resolution = [1920, 1080]"),
+            )
+            .deserialize::<SceneConfig>()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Value {
+                ref key,
+                layer: Some(ConfigLayer::Chapter),
+                ..
+            } if key == "general.resolution"
+        ));
+
+        let error = ConfigLayers { layers: Vec::new() }
+            .with(
+                ConfigLayer::Chapter,
+                toml("[general]\nresolution = \"1x1\""),
+            )
+            .deserialize::<SceneConfig>()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Value {
+                ref key,
+                layer: Some(ConfigLayer::Chapter),
+                ..
+            } if key == "general.fps"
+        ));
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(try_from = "String")]
+    struct Resolution;
+
+    impl TryFrom<String> for Resolution {
+        type Error = String;
+
+        fn try_from(resolution: String) -> Result<Self, Self::Error> {
+            match resolution.split_once('x') {
+                Some(_) => Ok(Self),
+                None => Err(format!("Malformed resolution `{resolution}`")),
+            }
+        }
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct ValidatedConfig {
+        #[allow(dead_code)]
+        general: ValidatedGeneralConfig,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct ValidatedGeneralConfig {
+        #[allow(dead_code)]
+        resolution: Resolution,
+    }
+
+    #[test]
+    fn test_custom_errors() {
+        let layers = ConfigLayers { layers: Vec::new() }.with(
+            ConfigLayer::Fallback,
+            toml("general.resolution = \"1920x1080\""),
+        );
+        assert!(layers.clone().deserialize::<ValidatedConfig>().is_ok());
+
+        let error = layers
+            .with_overrides(["general.resolution=abc"])
+            .unwrap()
+            .deserialize::<ValidatedConfig>()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Value {
+                ref key,
+                layer: Some(ConfigLayer::CommandLine),
+                ref error,
+                ..
+            } if key == "general.resolution" && error.to_string().contains("Malformed resolution `abc`")
+        ));
+    }
+}
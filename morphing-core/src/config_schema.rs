@@ -0,0 +1,197 @@
+use std::marker::PhantomData;
+
+use schemars::JsonSchema;
+use schemars::Schema;
+use schemars::SchemaGenerator;
+
+use super::link::SceneSymbol;
+use super::scene::Supervisor;
+
+// Schema of a top-level table in the fallback configs, e.g. `[general]` of the builtins.
+pub struct ConfigSchema {
+    pub key: &'static str,
+    pub schema: fn(&mut SchemaGenerator) -> Schema,
+}
+
+inventory::collect!(ConfigSchema);
+
+// Scene configs only contribute to the schema if they derive `JsonSchema`. `(&probe).scene_schema(..)`
+// resolves to `SceneSchema` when the bound holds, and to the autoref'd `SceneSchemaFallback` otherwise.
+pub struct SchemaProbe<C>(PhantomData<C>);
+
+impl<C> SchemaProbe<C> {
+    pub fn of<R>(_scene: fn(&mut Supervisor<C>) -> R) -> Self {
+        Self(PhantomData)
+    }
+}
+
+pub trait SceneSchema {
+    fn scene_schema(&self, generator: &mut SchemaGenerator) -> Option<Schema>;
+}
+
+impl<C> SceneSchema for SchemaProbe<C>
+where
+    C: JsonSchema,
+{
+    fn scene_schema(&self, generator: &mut SchemaGenerator) -> Option<Schema> {
+        Some(generator.subschema_for::<C>())
+    }
+}
+
+pub trait SceneSchemaFallback {
+    fn scene_schema(&self, generator: &mut SchemaGenerator) -> Option<Schema>;
+}
+
+impl<C> SceneSchemaFallback for &SchemaProbe<C> {
+    fn scene_schema(&self, _generator: &mut SchemaGenerator) -> Option<Schema> {
+        None
+    }
+}
+
+// Draft 7 is the most widely supported by editors, e.g. taplo.
+// Any layer may hold a partial config, so nothing is marked as required.
+fn schema_generator() -> SchemaGenerator {
+    let mut settings = schemars::generate::SchemaSettings::draft07();
+    settings
+        .transforms
+        .push(Box::new(schemars::transform::RecursiveTransform(
+            |schema: &mut Schema| {
+                schema.remove("required");
+            },
+        )));
+    SchemaGenerator::new(settings)
+}
+
+// A config file is valid if it matches the fallback tables and the config of every scene,
+// as each scene deserializes its own config from the same layers.
+pub(crate) fn chapter_schema<'a>(symbols: impl IntoIterator<Item = &'a SceneSymbol>) -> Schema {
+    let mut generator = schema_generator();
+    let properties: serde_json::Map<_, _> = inventory::iter::<ConfigSchema>
+        .into_iter()
+        .map(|config_schema| {
+            (
+                config_schema.key.to_string(),
+                (config_schema.schema)(&mut generator).into(),
+            )
+        })
+        .chain(std::iter::once((
+            "$schema".to_string(),
+            serde_json::json!({ "type": "string" }),
+        )))
+        .collect();
+    let scene_schemas: Vec<Schema> = symbols
+        .into_iter()
+        .filter_map(|symbol| (symbol.schema)(&mut generator))
+        .collect();
+    let mut schema = schemars::json_schema!({
+        "$schema": generator.settings().meta_schema,
+        "type": "object",
+        "properties": properties,
+    });
+    if !scene_schemas.is_empty() {
+        schema.insert("allOf".into(), scene_schemas.into());
+    }
+    let definitions = generator.take_definitions(true);
+    if !definitions.is_empty() {
+        schema.insert("definitions".into(), definitions.into());
+    }
+    schema
+}
+
+#[cfg(test)]
+mod config_schema_tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct ProbeTable {
+        width: u32,
+        height: Option<u32>,
+    }
+
+    inventory::submit! {
+        ConfigSchema {
+            key: "probe",
+            schema: |generator| generator.subschema_for::<ProbeTable>(),
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct SchemaConfig {
+        probe: ProbeTable,
+        title: String,
+    }
+
+    struct PlainConfig;
+
+    fn schema_scene(_sv: &mut Supervisor<SchemaConfig>) {}
+
+    fn plain_scene(_sv: &mut Supervisor<PlainConfig>) {}
+
+    // Expands the same way as `#[scene]`.
+    macro_rules! scene_schema {
+        ($scene:ident, $generator:expr) => {{
+            #[allow(unused_imports)]
+            use SceneSchema as _;
+            #[allow(unused_imports)]
+            use SceneSchemaFallback as _;
+            (&SchemaProbe::of($scene)).scene_schema($generator)
+        }};
+    }
+
+    fn scene_symbol(
+        name: &'static str,
+        schema: fn(&mut SchemaGenerator) -> Option<Schema>,
+    ) -> SceneSymbol {
+        SceneSymbol {
+            name,
+            config: Vec::new,
+            variants: || Ok(Vec::new()),
+            scene: |_| unreachable!(),
+            schema,
+        }
+    }
+
+    fn contains_key(value: &serde_json::Value, key: &str) -> bool {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.contains_key(key) || map.values().any(|value| contains_key(value, key))
+            }
+            serde_json::Value::Array(values) => values.iter().any(|value| contains_key(value, key)),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_schema_probe() {
+        let mut generator = schema_generator();
+        assert!(scene_schema!(schema_scene, &mut generator).is_some());
+        assert!(scene_schema!(plain_scene, &mut generator).is_none());
+    }
+
+    #[test]
+    fn test_chapter_schema() {
+        let symbols = [
+            scene_symbol("schema_scene", |generator| {
+                scene_schema!(schema_scene, generator)
+            }),
+            scene_symbol("plain_scene", |generator| {
+                scene_schema!(plain_scene, generator)
+            }),
+        ];
+        let schema = serde_json::Value::from(chapter_schema(&symbols));
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"]["probe"].is_object());
+        assert!(schema["properties"]["$schema"].is_object());
+        // Only scenes with a `JsonSchema` config contribute.
+        assert_eq!(schema["allOf"].as_array().unwrap().len(), 1);
+        assert!(
+            schema["definitions"]
+                .as_object()
+                .unwrap()
+                .contains_key("SchemaConfig")
+        );
+        assert!(!contains_key(&schema, "required"));
+    }
+}
@@ -0,0 +1,33 @@
+// Type-level field name, hashed from the identifier by `#[derive(FieldIndex)]` and `fp!`.
+// A field path is either a single key or a tuple `(key, path)`, and indexes through
+// the `Index` and `IndexMut` impls generated by `#[derive(FieldIndex)]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Key<const KEY: u64>;
+
+#[cfg(test)]
+mod field_index_tests {
+    #[derive(crate::FieldIndex)]
+    struct Inner {
+        value: f32,
+    }
+
+    #[derive(crate::FieldIndex)]
+    struct Outer {
+        inner: Inner,
+        count: u32,
+    }
+
+    #[test]
+    fn test_field_path() {
+        let mut outer = Outer {
+            inner: Inner { value: 1.0 },
+            count: 2,
+        };
+        assert_eq!(outer[crate::fp!(count)], 2);
+        assert_eq!(outer[crate::fp!(inner.value)], 1.0);
+        outer[crate::fp!(inner.value)] = 3.0;
+        outer[crate::fp!(count)] += 1;
+        assert_eq!(outer.inner.value, 3.0);
+        assert_eq!(outer.count, 3);
+    }
+}
@@ -0,0 +1,134 @@
+// Blends two states of a value. `alpha` is expected to lie in `[0, 1]`,
+// where `0` yields `self` and `1` yields `other`.
+pub trait Interpolate {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self;
+}
+
+// Switches from `this` to `other` halfway, for values without a continuous blend.
+pub fn interpolate_step<T>(this: &T, other: &T, alpha: f32) -> T
+where
+    T: Clone,
+{
+    if alpha < 0.5 {
+        this.clone()
+    } else {
+        other.clone()
+    }
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        self + (other - self) * alpha
+    }
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        self + (other - self) * alpha as f64
+    }
+}
+
+impl<T, const N: usize> Interpolate for [T; N]
+where
+    T: Interpolate,
+{
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        std::array::from_fn(|i| self[i].interpolate(&other[i], alpha))
+    }
+}
+
+impl<T0, T1> Interpolate for (T0, T1)
+where
+    T0: Interpolate,
+    T1: Interpolate,
+{
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        (
+            self.0.interpolate(&other.0, alpha),
+            self.1.interpolate(&other.1, alpha),
+        )
+    }
+}
+
+impl<T, const R: usize, const C: usize> Interpolate for nalgebra::SMatrix<T, R, C>
+where
+    T: nalgebra::Scalar + Interpolate,
+{
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        self.zip_map(other, |a, b| a.interpolate(&b, alpha))
+    }
+}
+
+#[cfg(test)]
+mod interpolate_tests {
+    use super::Interpolate;
+
+    #[derive(Clone, Debug, PartialEq, crate::Interpolate)]
+    struct Named {
+        value: f32,
+        point: nalgebra::Vector2<f32>,
+        #[interpolate(step)]
+        label: &'static str,
+        #[interpolate(with = "interpolate_squared")]
+        squared: f32,
+    }
+
+    #[derive(Clone, Debug, PartialEq, crate::Interpolate)]
+    struct Unnamed<T>(T, #[interpolate(step)] bool);
+
+    // Interpolates the square roots instead.
+    fn interpolate_squared(this: &f32, other: &f32, alpha: f32) -> f32 {
+        this.sqrt().interpolate(&other.sqrt(), alpha).powi(2)
+    }
+
+    fn endpoints() -> (Named, Named) {
+        (
+            Named {
+                value: 1.0,
+                point: nalgebra::Vector2::new(0.0, 2.0),
+                label: "start",
+                squared: 1.0,
+            },
+            Named {
+                value: 3.0,
+                point: nalgebra::Vector2::new(4.0, -2.0),
+                label: "end",
+                squared: 9.0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let (start, end) = endpoints();
+        assert_eq!(start.interpolate(&end, 0.0), start);
+        assert_eq!(start.interpolate(&end, 1.0), end);
+        assert_eq!(
+            Unnamed(1.0, false).interpolate(&Unnamed(2.0, true), 0.0),
+            Unnamed(1.0, false)
+        );
+        assert_eq!(
+            Unnamed(1.0, false).interpolate(&Unnamed(2.0, true), 1.0),
+            Unnamed(2.0, true)
+        );
+    }
+
+    #[test]
+    fn test_derive() {
+        let (start, end) = endpoints();
+        assert_eq!(
+            start.interpolate(&end, 0.5),
+            Named {
+                value: 2.0,
+                point: nalgebra::Vector2::new(2.0, 0.0),
+                label: "end",
+                squared: 4.0,
+            }
+        );
+        assert_eq!(start.interpolate(&end, 0.25).label, "start");
+        assert_eq!(
+            Unnamed([0.0, 4.0], false).interpolate(&Unnamed([2.0, 0.0], true), 0.25),
+            Unnamed([0.5, 3.0], false)
+        );
+    }
+}
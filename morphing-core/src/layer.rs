@@ -0,0 +1,70 @@
+// A layer collects the presentations drawn with one pipeline, one `Vec` per channel,
+// and usually serves as the `Lifecycle::Resource` of mobjects.
+// `#[layer]` declares the channels, while `render` is written by hand.
+pub trait Layer: 'static + Default + Send + Sync {
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>);
+}
+
+// Names a channel of the layer `L`, generated by `#[layer]` as `<Layer><Field>Channel`.
+pub trait ChannelIndex<L> {
+    type Presentation: 'static + Send + Sync;
+
+    fn channel(layer: &L) -> &Vec<Self::Presentation>;
+    fn channel_mut(layer: &mut L) -> &mut Vec<Self::Presentation>;
+}
+
+// A stack of layers, rendered in the order of declaration. Implemented by `#[world]`.
+pub trait World: 'static + Default + Send + Sync {
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>);
+}
+
+// Names a layer of the world `W`, generated by `#[world]` as `<World><Field>Layer`.
+pub trait LayerIndex<W> {
+    type Layer: Layer;
+
+    fn layer(world: &W) -> &Self::Layer;
+    fn layer_mut(world: &mut W) -> &mut Self::Layer;
+}
+
+#[cfg(test)]
+mod layer_tests {
+    use super::ChannelIndex;
+    use super::Layer;
+    use super::LayerIndex;
+
+    #[crate::layer]
+    struct ProbeLayer {
+        numbers: u32,
+        names: &'static str,
+    }
+
+    impl Layer for ProbeLayer {
+        fn render(&self, _render_pass: &mut wgpu::RenderPass<'_>) {}
+    }
+
+    #[crate::world]
+    struct ProbeWorld {
+        lower: ProbeLayer,
+        upper: ProbeLayer,
+    }
+
+    #[test]
+    fn test_channel_index() {
+        let mut layer = ProbeLayer::default();
+        ProbeLayerNumbersChannel::channel_mut(&mut layer).push(1);
+        ProbeLayerNamesChannel::channel_mut(&mut layer).push("a");
+        assert_eq!(ProbeLayerNumbersChannel::channel(&layer), &vec![1]);
+        assert_eq!(ProbeLayerNamesChannel::channel(&layer), &vec!["a"]);
+    }
+
+    #[test]
+    fn test_layer_index() {
+        let mut world = ProbeWorld::default();
+        ProbeLayerNumbersChannel::channel_mut(ProbeWorldUpperLayer::layer_mut(&mut world)).push(2);
+        assert!(ProbeLayerNumbersChannel::channel(ProbeWorldLowerLayer::layer(&world)).is_empty());
+        assert_eq!(
+            ProbeLayerNumbersChannel::channel(ProbeWorldUpperLayer::layer(&world)),
+            &vec![2]
+        );
+    }
+}
@@ -1,11 +1,106 @@
+mod cargo;
+mod component;
+mod config_layers;
+mod config_schema;
+mod field_index;
+mod interpolate;
+mod layer;
+mod link;
+mod rate;
+mod renderer;
+mod runner;
 mod scene;
 
 extern crate self as morphing;
 
+pub use cargo::build_chapter;
+pub use cargo::ChapterBuildError;
+pub use component::Component;
+pub use component::ComponentShaderTypes;
+pub use component::WgslBinding;
+pub use component::WgslMember;
+pub use component::WgslStruct;
+pub use config_layers::ConfigError;
+pub use config_layers::ConfigFallbackContent;
+pub use config_layers::ConfigFile;
+pub use config_layers::ConfigLayer;
+pub use config_layers::ConfigLayers;
+pub use config_schema::ConfigSchema;
+pub use field_index::Key;
+pub use interpolate::interpolate_step;
+pub use interpolate::Interpolate;
+pub use layer::ChannelIndex;
+pub use layer::Layer;
+pub use layer::LayerIndex;
+pub use layer::World;
+pub use link::call_entrypoint;
+pub use link::AbiDescriptor;
+pub use link::ChapterLoadError;
+pub use link::ChapterSymbol;
+pub use link::SceneEntry;
+pub use link::SceneError;
+pub use link::SceneResult;
+pub use link::SceneSymbol;
+pub use link::SceneVariant;
+pub use rate::ApplyRate;
+pub use rate::Blend;
+pub use rate::Clamp;
+pub use rate::DenormalizedTimeMetric;
+pub use rate::IdentityRate;
+pub use rate::IncreasingRate;
+pub use rate::Lag;
+pub use rate::Mirror;
+pub use rate::NormalizedOutputRateCombinators;
+pub use rate::NormalizedRateCombinators;
+pub use rate::NormalizedTimeMetric;
+pub use rate::Rate;
+pub use rate::RateCombinators;
+pub use rate::Rated;
+pub use rate::Repeat;
+pub use rate::Reverse;
+pub use rate::Then;
+pub use rate::ThereAndBack;
+pub use rate::TimeMetric;
+pub use renderer::Renderer;
+pub use renderer::RendererError;
+pub use renderer::TEXTURE_FORMAT;
+pub use runner::read_and_deserialize;
+pub use runner::run_isolated;
+pub use runner::runner_mode;
+pub use runner::Failure;
+pub use runner::LineOutput;
+pub use runner::RedirectedOutput;
+pub use runner::SceneData;
+pub use scene::ErasedLifecycle;
+pub use scene::ErasedResource;
+pub use scene::Handle;
 pub use scene::Lifecycle;
+pub use scene::MobjectBuilder;
+pub use scene::Supervisor;
+pub use scene::Time;
+pub use scene::Timeline;
+pub use scene::TimelineResources;
 
-pub use morphing_macros::{chapter, fp, scene, FieldIndex};
+pub use morphing_macros::{
+    chapter, fp, include_wgsl, layer, rate, scene, wgpu_shader_types, wgpu_struct, world, FieldIndex,
+    Interpolate,
+};
 
 // For macro invocation internal usage
 #[doc(hidden)]
 pub use morphing_macros as __macros;
+#[doc(hidden)]
+pub use wgpu as __wgpu;
+#[doc(hidden)]
+pub mod __link {
+    pub use config;
+    pub use inventory;
+
+    pub use super::config_schema::SceneSchema;
+    pub use super::config_schema::SceneSchemaFallback;
+    pub use super::config_schema::SchemaProbe;
+    pub use super::link::chapter_symbol;
+    pub use super::link::run_scene;
+    pub use super::link::variants_from_file;
+    pub use super::link::variants_from_list;
+}
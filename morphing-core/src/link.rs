@@ -0,0 +1,603 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::ffi::c_char;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use super::config_layers::ConfigError;
+use super::config_layers::ConfigFile;
+use super::config_layers::ConfigLayer;
+use super::config_layers::ConfigLayers;
+use super::config_schema::chapter_schema;
+use super::scene::Supervisor;
+use super::scene::Timeline;
+
+// Registered by `#[scene]`. As `inventory::submit!` requires a constant expression,
+// everything that allocates is deferred behind function pointers.
+pub struct SceneSymbol {
+    pub name: &'static str,
+    pub config: fn() -> Vec<ConfigFile>,
+    pub variants: fn() -> Result<Vec<SceneVariant>, ConfigError>,
+    pub scene: fn(&ConfigLayers) -> Result<Timeline, SceneError>,
+    pub schema: fn(&mut schemars::SchemaGenerator) -> Option<schemars::Schema>,
+}
+
+inventory::collect!(SceneSymbol);
+
+// A config table overlaid onto the scene config, registered as `name[variant]`.
+#[derive(Clone, Debug)]
+pub struct SceneVariant {
+    name: String,
+    config: config::Map<String, config::Value>,
+}
+
+impl SceneVariant {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl config::Source for SceneVariant {
+    fn clone_into_box(&self) -> Box<dyn config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<config::Map<String, config::Value>, config::ConfigError> {
+        Ok(self.config.clone())
+    }
+}
+
+// Failures are reported as errors of the variant layer, attached to the scene entry.
+fn variant_error(error: config::ConfigError) -> ConfigError {
+    ConfigError::Layer {
+        layer: ConfigLayer::Variant,
+        error: Box::new(error),
+    }
+}
+
+// Every top-level table of the file is a variant.
+pub fn variants_from_file(file: ConfigFile) -> Result<Vec<SceneVariant>, ConfigError> {
+    Ok(config::Config::builder()
+        .add_source(file)
+        .build()
+        .and_then(|config| {
+            config.try_deserialize::<config::Map<String, config::Map<String, config::Value>>>()
+        })
+        .map_err(variant_error)?
+        .into_iter()
+        .map(|(name, config)| SceneVariant { name, config })
+        .collect())
+}
+
+pub fn variants_from_list<const N: usize>(
+    variants: [(&str, Vec<ConfigFile>); N],
+) -> Result<Vec<SceneVariant>, ConfigError> {
+    variants
+        .into_iter()
+        .map(|(name, config)| {
+            Ok(SceneVariant {
+                name: name.into(),
+                config: config::Config::builder()
+                    .add_source(config)
+                    .build()
+                    .and_then(|config| config::Source::collect(&config))
+                    .map_err(variant_error)?,
+            })
+        })
+        .collect()
+}
+
+// Scenes return either `()` or `Result<(), E>` with `E: Display`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a valid scene return type",
+    label = "expected `()` or `Result<(), E>` with `E: Display`"
+)]
+pub trait SceneResult {
+    fn into_result(self) -> Result<(), String>;
+}
+
+impl SceneResult for () {
+    fn into_result(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<E> SceneResult for Result<(), E>
+where
+    E: std::fmt::Display,
+{
+    fn into_result(self) -> Result<(), String> {
+        self.map_err(|error| error.to_string())
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Config(ConfigError),
+    // Returned by the scene.
+    Error(String),
+    Panic {
+        message: String,
+        location: Option<String>,
+    },
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Config(error) => write!(f, "{error}"),
+            Self::Error(message) => write!(f, "{message}"),
+            Self::Panic {
+                message,
+                location: Some(location),
+            } => write!(f, "Panicked at {location}: {message}"),
+            Self::Panic {
+                message,
+                location: None,
+            } => write!(f, "Panicked: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Config(error) => Some(error),
+            Self::Error(_) | Self::Panic { .. } => None,
+        }
+    }
+}
+
+impl From<ConfigError> for SceneError {
+    fn from(error: ConfigError) -> Self {
+        Self::Config(error)
+    }
+}
+
+thread_local! {
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+type PanicHook = Box<dyn Fn(&std::panic::PanicHookInfo<'_>) + Send + Sync>;
+
+static PANIC_HOOK_USERS: Mutex<usize> = Mutex::new(0);
+static PREVIOUS_PANIC_HOOK: Mutex<Option<PanicHook>> = Mutex::new(None);
+
+// Records the location of the panic, then reports it through the previous hook.
+fn record_panic_location(info: &std::panic::PanicHookInfo<'_>) {
+    PANIC_LOCATION.replace(info.location().map(ToString::to_string));
+    if let Some(previous_hook) = PREVIOUS_PANIC_HOOK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        previous_hook(info);
+    }
+}
+
+// Keeps `record_panic_location` installed until dropped. Hooks are process-wide, while this code
+// is compiled into the chapter library, which is unloaded on hot reload. So the hook must not
+// outlive the call. Nested and concurrent calls share one installation.
+struct PanicHookGuard;
+
+impl PanicHookGuard {
+    fn install() -> Self {
+        let mut users = PANIC_HOOK_USERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if *users == 0 {
+            *PREVIOUS_PANIC_HOOK
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(std::panic::take_hook());
+            std::panic::set_hook(Box::new(record_panic_location));
+        }
+        *users += 1;
+        Self
+    }
+}
+
+impl Drop for PanicHookGuard {
+    fn drop(&mut self) {
+        let mut users = PANIC_HOOK_USERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *users -= 1;
+        if *users == 0 {
+            drop(std::panic::take_hook());
+            if let Some(previous_hook) = PREVIOUS_PANIC_HOOK
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take()
+            {
+                std::panic::set_hook(previous_hook);
+            }
+        }
+    }
+}
+
+// Like `std::panic::catch_unwind`, also recovering the message and location of the panic.
+pub(crate) fn catch_unwind<T>(f: impl FnOnce() -> T) -> Result<T, SceneError> {
+    let panic_hook_guard = PanicHookGuard::install();
+    let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+    drop(panic_hook_guard);
+    result.map_err(|payload| SceneError::Panic {
+        message: payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".into()),
+        location: PANIC_LOCATION.take(),
+    })
+}
+
+// Called from the closure registered by `#[scene]`, so that panics are caught on the side of
+// the chapter library, where they are raised.
+pub fn run_scene<C, R>(
+    layers: &ConfigLayers,
+    scene: fn(&mut Supervisor<C>) -> R,
+) -> Result<Timeline, SceneError>
+where
+    C: serde::de::DeserializeOwned,
+    R: SceneResult,
+{
+    let mut supervisor = Supervisor::new(layers.deserialize()?);
+    catch_unwind(|| scene(&mut supervisor))?
+        .into_result()
+        .map_err(SceneError::Error)?;
+    Ok(supervisor.into_timeline())
+}
+
+// A scene registered in the chapter, possibly specialized to one of its variants.
+// If the variants failed to load, the scene is registered once under its own name, holding the error.
+pub struct SceneEntry {
+    symbol: &'static SceneSymbol,
+    variant: Result<Option<SceneVariant>, ConfigError>,
+}
+
+impl SceneEntry {
+    // `layers` are expected to hold the chapter config, see `ChapterSymbol::config_layers`.
+    pub fn timeline(&self, layers: ConfigLayers) -> Result<Timeline, SceneError> {
+        if self.variant.is_err() {
+            // `ConfigError` is not `Clone`. Variant configs are embedded in the chapter library,
+            // so loading them again reproduces the error.
+            (self.symbol.variants)()?;
+        }
+        (self.symbol.scene)(&self.config_layers(layers))
+    }
+
+    pub fn config_error(&self) -> Option<&ConfigError> {
+        self.variant.as_ref().err()
+    }
+
+    // The layers the scene is run with, e.g. to read the `[general]` table as the scene sees it.
+    pub fn config_layers(&self, layers: ConfigLayers) -> ConfigLayers {
+        let layers = (self.symbol.config)()
+            .into_iter()
+            .fold(layers, |layers, file| layers.with(ConfigLayer::Scene, file));
+        match &self.variant {
+            Ok(Some(variant)) => layers.with(ConfigLayer::Variant, variant.clone()),
+            Ok(None) | Err(_) => layers,
+        }
+    }
+}
+
+pub struct ChapterSymbol {
+    name: String,
+    config: Vec<ConfigFile>,
+    scenes: HashMap<String, SceneEntry>,
+    schema: schemars::Schema,
+    emit_schema: bool,
+    // Set by the loader. Declared last so that it is dropped after everything pointing into it.
+    library: Option<Arc<libloading::Library>>,
+}
+
+impl ChapterSymbol {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scenes(&self) -> &HashMap<String, SceneEntry> {
+        &self.scenes
+    }
+
+    // Values produced by the scenes, e.g. timelines, hold vtables from the library,
+    // and should keep this handle alongside if they may outlive the chapter.
+    pub fn library(&self) -> Option<Arc<libloading::Library>> {
+        self.library.clone()
+    }
+
+    // Merged schema of the fallback tables and all scene configs.
+    pub fn schema(&self) -> &schemars::Schema {
+        &self.schema
+    }
+
+    pub fn write_schema(&self, path: &Path) -> std::io::Result<()> {
+        let content = serde_json::to_string_pretty(&self.schema)?;
+        // Leave the file untouched if unchanged, so that editors do not reload it.
+        if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+            return Ok(());
+        }
+        std::fs::write(path, content)
+    }
+
+    // Writes `<chapter>.schema.json` into `dir`, usually the target dir, if requested by `#[chapter(schema)]`.
+    pub fn emit_schema(&self, dir: &Path) -> std::io::Result<()> {
+        if !self.emit_schema {
+            return Ok(());
+        }
+        self.write_schema(&dir.join(format!("{}.schema.json", self.name)))
+    }
+
+    // Fallbacks, the chapter config and the environment.
+    pub fn config_layers(&self) -> ConfigLayers {
+        self.config
+            .iter()
+            .cloned()
+            .fold(ConfigLayers::new(), |layers, file| {
+                layers.with(ConfigLayer::Chapter, file)
+            })
+    }
+}
+
+// Runs inside the chapter library, where the `inventory` registries are populated.
+pub fn chapter_symbol(name: &str, config: Vec<ConfigFile>, emit_schema: bool) -> ChapterSymbol {
+    ChapterSymbol {
+        name: name.into(),
+        config,
+        scenes: inventory::iter::<SceneSymbol>
+            .into_iter()
+            .flat_map(|symbol| match (symbol.variants)() {
+                Ok(variants) if !variants.is_empty() => variants
+                    .into_iter()
+                    .map(|variant| {
+                        (
+                            format!("{}[{}]", symbol.name, variant.name),
+                            SceneEntry {
+                                symbol,
+                                variant: Ok(Some(variant)),
+                            },
+                        )
+                    })
+                    .collect(),
+                variants => vec![(
+                    symbol.name.to_string(),
+                    SceneEntry {
+                        symbol,
+                        variant: variants.map(|_| None),
+                    },
+                )],
+            })
+            .collect(),
+        schema: chapter_schema(inventory::iter::<SceneSymbol>),
+        emit_schema,
+        library: None,
+    }
+}
+
+// Both symbols are emitted by `#[chapter]`. Bump the entrypoint version, here and in
+// `morphing-macros`, whenever `AbiDescriptor` cannot tell apart incompatible chapters.
+const ENTRYPOINT_VERSION: u32 = 1;
+const ABI_SYMBOL: &str = "__morphing_abi__";
+const ENTRYPOINT_SYMBOL: &str = "__morphing_entrypoint_v1__";
+
+// Exported by chapters and checked before the entrypoint is looked up.
+// The layout is fixed across versions, hence only C-compatible fields.
+#[repr(C)]
+pub struct AbiDescriptor {
+    pub entrypoint_version: u32,
+    pub core_version: *const c_char,
+    pub rustc_version: *const c_char,
+    pub layout_hash: u64,
+}
+
+// Only ever points to static strings.
+unsafe impl Sync for AbiDescriptor {}
+
+impl AbiDescriptor {
+    pub const CURRENT: Self = Self {
+        entrypoint_version: ENTRYPOINT_VERSION,
+        core_version: concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast(),
+        rustc_version: concat!(env!("MORPHING_RUSTC_VERSION"), "\0")
+            .as_ptr()
+            .cast(),
+        layout_hash: layout_hash(),
+    };
+
+    fn check(&self) -> Result<(), ChapterLoadError> {
+        let current = Self::CURRENT;
+        if self.entrypoint_version != current.entrypoint_version {
+            return Err(ChapterLoadError::AbiMismatch {
+                what: "entrypoint version",
+                chapter: self.entrypoint_version.to_string(),
+                host: current.entrypoint_version.to_string(),
+            });
+        }
+        // Safety: both sides agree on the entrypoint version, hence on this layout.
+        let strings = unsafe {
+            [
+                (
+                    "morphing-core version",
+                    self.core_version,
+                    current.core_version,
+                ),
+                ("rustc version", self.rustc_version, current.rustc_version),
+            ]
+            .map(|(what, chapter, host)| {
+                (
+                    what,
+                    CStr::from_ptr(chapter).to_string_lossy().into_owned(),
+                    CStr::from_ptr(host).to_string_lossy().into_owned(),
+                )
+            })
+        };
+        let layout_hashes = (
+            "symbol layout hash",
+            format!("{:016x}", self.layout_hash),
+            format!("{:016x}", current.layout_hash),
+        );
+        for (what, chapter, host) in strings.into_iter().chain(std::iter::once(layout_hashes)) {
+            if chapter != host {
+                return Err(ChapterLoadError::AbiMismatch {
+                    what,
+                    chapter,
+                    host,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+// FNV-1a over the layouts of the types passed across the library boundary.
+const fn layout_hash() -> u64 {
+    let layout = [
+        size_of::<SceneSymbol>(),
+        align_of::<SceneSymbol>(),
+        std::mem::offset_of!(SceneSymbol, name),
+        std::mem::offset_of!(SceneSymbol, config),
+        std::mem::offset_of!(SceneSymbol, variants),
+        std::mem::offset_of!(SceneSymbol, scene),
+        std::mem::offset_of!(SceneSymbol, schema),
+        size_of::<SceneEntry>(),
+        size_of::<ChapterSymbol>(),
+        align_of::<ChapterSymbol>(),
+        size_of::<ConfigLayers>(),
+        size_of::<Timeline>(),
+    ];
+    let mut hash = 0xcbf29ce484222325_u64;
+    let mut i = 0;
+    while i < layout.len() {
+        let bytes = (layout[i] as u64).to_le_bytes();
+        let mut j = 0;
+        while j < bytes.len() {
+            hash ^= bytes[j] as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+            j += 1;
+        }
+        i += 1;
+    }
+    hash
+}
+
+#[derive(Debug)]
+pub enum ChapterLoadError {
+    Library(libloading::Error),
+    MissingSymbol {
+        symbol: &'static str,
+        error: libloading::Error,
+    },
+    AbiMismatch {
+        what: &'static str,
+        chapter: String,
+        host: String,
+    },
+}
+
+impl std::fmt::Display for ChapterLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Library(error) => write!(f, "Failed to open chapter library: {error}"),
+            Self::MissingSymbol { symbol, error } => write!(
+                f,
+                "Missing `{symbol}` in chapter library, expecting a `#[chapter]` invocation \
+                built against a compatible morphing-core: {error}"
+            ),
+            Self::AbiMismatch {
+                what,
+                chapter,
+                host,
+            } => write!(
+                f,
+                "Chapter was built with {what} `{chapter}`, but the host uses `{host}`; \
+                rebuild the chapter against the same morphing-core and toolchain"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChapterLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Library(error) | Self::MissingSymbol { error, .. } => Some(error),
+            Self::AbiMismatch { .. } => None,
+        }
+    }
+}
+
+pub fn call_entrypoint(chapter_path: impl AsRef<Path>) -> Result<ChapterSymbol, ChapterLoadError> {
+    let missing_symbol = |symbol| move |error| ChapterLoadError::MissingSymbol { symbol, error };
+    // Safety: the descriptor is checked before anything with a Rust layout is touched.
+    unsafe {
+        let library =
+            libloading::Library::new(chapter_path.as_ref()).map_err(ChapterLoadError::Library)?;
+        let descriptor: *const AbiDescriptor = *library
+            .get::<*const AbiDescriptor>(ABI_SYMBOL.as_bytes())
+            .map_err(missing_symbol(ABI_SYMBOL))?;
+        (*descriptor).check()?;
+        let entrypoint: extern "Rust" fn() -> ChapterSymbol = *library
+            .get::<extern "Rust" fn() -> ChapterSymbol>(ENTRYPOINT_SYMBOL.as_bytes())
+            .map_err(missing_symbol(ENTRYPOINT_SYMBOL))?;
+        let mut chapter = entrypoint();
+        chapter.library = Some(Arc::new(library));
+        Ok(chapter)
+    }
+}
+
+#[cfg(test)]
+mod link_tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_results() {
+        assert_eq!(().into_result(), Ok(()));
+        assert_eq!(
+            Err::<(), _>("Missing asset").into_result(),
+            Err("Missing asset".to_string())
+        );
+        assert!(matches!(
+            catch_unwind(|| panic!("boom")),
+            Err(SceneError::Panic {
+                message,
+                location: Some(location),
+            }) if message == "boom" && location.starts_with(file!())
+        ));
+        assert!(matches!(catch_unwind(|| 1), Ok(1)));
+    }
+
+    #[test]
+    fn test_variants() {
+        let variants = variants_from_file(config::File::from_str(
+            "[dark]\nbackground = \"#000000\"\n[light]\nbackground = \"#ffffff\"",
+            config::FileFormat::Toml,
+        ))
+        .unwrap();
+        assert_eq!(
+            variants.iter().map(SceneVariant::name).collect::<Vec<_>>(),
+            ["dark", "light"]
+        );
+        assert!(matches!(
+            variants_from_file(config::File::from_str("[dark", config::FileFormat::Toml)),
+            Err(ConfigError::Layer {
+                layer: ConfigLayer::Variant,
+                ..
+            })
+        ));
+        assert!(matches!(
+            variants_from_list([(
+                "dark",
+                vec![config::File::from_str(
+                    "background =",
+                    config::FileFormat::Toml
+                )]
+            )]),
+            Err(ConfigError::Layer {
+                layer: ConfigLayer::Variant,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,506 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use super::scene::Lifecycle;
+use super::scene::Time;
+
+// Played animations are signaled with their progress normalized into `[0, 1]`,
+// while spawned lifecycles are signaled with the seconds elapsed since they are spawned.
+pub trait TimeMetric: 'static + Copy + Deref<Target = Time> + From<Time> + Send + Sync {}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
+pub struct NormalizedTimeMetric(pub Time);
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
+pub struct DenormalizedTimeMetric(pub Time);
+
+impl TimeMetric for NormalizedTimeMetric {}
+
+impl TimeMetric for DenormalizedTimeMetric {}
+
+impl Deref for NormalizedTimeMetric {
+    type Target = Time;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for DenormalizedTimeMetric {
+    type Target = Time;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Time> for NormalizedTimeMetric {
+    fn from(time: Time) -> Self {
+        Self(time)
+    }
+}
+
+impl From<Time> for DenormalizedTimeMetric {
+    fn from(time: Time) -> Self {
+        Self(time)
+    }
+}
+
+pub trait Rate<TM>: 'static + Send + Sync
+where
+    TM: TimeMetric,
+{
+    type OutputTimeMetric: TimeMetric;
+
+    fn eval(&self, time_metric: TM) -> Self::OutputTimeMetric;
+}
+
+// Marks rates that never decrease, which makes them invertible.
+pub trait IncreasingRate<TM>: Rate<TM>
+where
+    TM: TimeMetric,
+{
+    // Earliest non-negative input at which the output reaches `output`.
+    // The default bisects over `[0, 1]`, doubling the upper bound while it falls short.
+    fn inverse(&self, output: Self::OutputTimeMetric) -> TM {
+        let output = *output;
+        let eval = |time: Time| *self.eval(time.into());
+        if eval(0.0) >= output {
+            return 0.0.into();
+        }
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..MAX_INVERSE_ITERATIONS {
+            if eval(high) >= output {
+                break;
+            }
+            (low, high) = (high, 2.0 * high);
+        }
+        for _ in 0..MAX_INVERSE_ITERATIONS {
+            let middle = 0.5 * (low + high);
+            if middle <= low || middle >= high {
+                break;
+            }
+            if eval(middle) < output {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        high.into()
+    }
+}
+
+const MAX_INVERSE_ITERATIONS: usize = 64;
+
+// Implemented by anything a rate can be applied to, which the methods generated by `#[rate]` build on.
+pub trait ApplyRate<TM>: Sized
+where
+    TM: TimeMetric,
+{
+    type Output<R>
+    where
+        R: Rate<TM>;
+
+    fn apply_rate<R>(self, rate: R) -> Self::Output<R>
+    where
+        R: Rate<TM>;
+}
+
+// Starting point to obtain rates as values, e.g. `IdentityRate.smooth()`.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct IdentityRate;
+
+impl<TM> ApplyRate<TM> for IdentityRate
+where
+    TM: TimeMetric,
+{
+    type Output<R>
+        = R
+    where
+        R: Rate<TM>;
+
+    fn apply_rate<R>(self, rate: R) -> Self::Output<R>
+    where
+        R: Rate<TM>,
+    {
+        rate
+    }
+}
+
+// A lifecycle whose signal is remapped by a rate before being passed on.
+pub struct Rated<L, TM, R> {
+    lifecycle: L,
+    rate: R,
+    time_metric: PhantomData<fn() -> TM>,
+}
+
+impl<L, TM> ApplyRate<TM> for L
+where
+    L: Lifecycle,
+    TM: TimeMetric,
+{
+    type Output<R>
+        = Rated<L, TM, R>
+    where
+        R: Rate<TM>;
+
+    fn apply_rate<R>(self, rate: R) -> Self::Output<R>
+    where
+        R: Rate<TM>,
+    {
+        Rated {
+            lifecycle: self,
+            rate,
+            time_metric: PhantomData,
+        }
+    }
+}
+
+impl<L, TM, R> Lifecycle for Rated<L, TM, R>
+where
+    L: Lifecycle,
+    TM: TimeMetric,
+    R: Rate<TM>,
+{
+    type Signal = Time;
+    type Resource = L::Resource;
+
+    fn setup(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Self::Resource {
+        self.lifecycle.setup(device, queue)
+    }
+
+    fn prepare(
+        &self,
+        signal: Self::Signal,
+        resource: &mut Self::Resource,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let signal = *self.rate.eval(signal.into());
+        self.lifecycle
+            .prepare(signal.into(), resource, device, queue);
+    }
+
+    fn render(&self, resource: &Self::Resource, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.lifecycle.render(resource, render_pass);
+    }
+}
+
+// Combinators over rates. Those defined on normalized time only remap the input within `[0, 1]`,
+// and `IncreasingRate` is implemented for a combinator exactly when it preserves monotonicity.
+pub trait RateCombinators<TM>: Rate<TM> + Sized
+where
+    TM: TimeMetric,
+{
+    // Applies `self` and then `rate` on its output.
+    fn then<R>(self, rate: R) -> Then<Self, R>
+    where
+        R: Rate<Self::OutputTimeMetric>,
+    {
+        Then(self, rate)
+    }
+
+    // `t -> (1 - weight) * self(t) + weight * rate(t)`, with `weight` in `[0, 1]`.
+    fn blend<R>(self, rate: R, weight: f32) -> Blend<Self, R>
+    where
+        R: Rate<TM, OutputTimeMetric = Self::OutputTimeMetric>,
+    {
+        assert!(
+            (0.0..=1.0).contains(&weight),
+            "Blend weight should lie in [0, 1]: {weight}"
+        );
+        Blend(self, rate, weight)
+    }
+}
+
+impl<TM, R> RateCombinators<TM> for R
+where
+    TM: TimeMetric,
+    R: Rate<TM>,
+{
+}
+
+pub trait NormalizedRateCombinators: Rate<NormalizedTimeMetric> + Sized {
+    // `t -> self(1 - t)`.
+    fn reverse(self) -> Reverse<Self> {
+        Reverse(self)
+    }
+
+    // Plays forth in the first half and back in the second half.
+    fn there_and_back(self) -> ThereAndBack<Self> {
+        ThereAndBack(self)
+    }
+
+    // Plays `count` times in succession.
+    fn repeat(self, count: u32) -> Repeat<Self> {
+        assert!(count > 0, "Cannot repeat zero times");
+        Repeat(self, count)
+    }
+
+    // Delays the start by `ratio` of the duration and plays in the rest.
+    fn lag(self, ratio: f32) -> Lag<Self> {
+        assert!(
+            (0.0..1.0).contains(&ratio),
+            "Lag ratio should lie in [0, 1): {ratio}"
+        );
+        Lag(self, ratio)
+    }
+}
+
+impl<R> NormalizedRateCombinators for R where R: Rate<NormalizedTimeMetric> {}
+
+pub trait NormalizedOutputRateCombinators:
+    Rate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric> + Sized
+{
+    // Clamps the output into `[0, 1]`, e.g. to cut off the overshoot of `back` or `elastic`.
+    fn clamp(self) -> Clamp<Self> {
+        Clamp(self)
+    }
+
+    // `t -> 1 - self(1 - t)`, turning an ease-in into an ease-out and vice versa.
+    fn mirror(self) -> Mirror<Self> {
+        Mirror(self)
+    }
+}
+
+impl<R> NormalizedOutputRateCombinators for R where
+    R: Rate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric>
+{
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Then<R0, R1>(R0, R1);
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Blend<R0, R1>(R0, R1, f32);
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Reverse<R>(R);
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ThereAndBack<R>(R);
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Repeat<R>(R, u32);
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Lag<R>(R, f32);
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Clamp<R>(R);
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Mirror<R>(R);
+
+impl<TM, R0, R1> Rate<TM> for Then<R0, R1>
+where
+    TM: TimeMetric,
+    R0: Rate<TM>,
+    R1: Rate<R0::OutputTimeMetric>,
+{
+    type OutputTimeMetric = R1::OutputTimeMetric;
+
+    fn eval(&self, time_metric: TM) -> Self::OutputTimeMetric {
+        self.1.eval(self.0.eval(time_metric))
+    }
+}
+
+impl<TM, R0, R1> IncreasingRate<TM> for Then<R0, R1>
+where
+    TM: TimeMetric,
+    R0: IncreasingRate<TM>,
+    R1: IncreasingRate<R0::OutputTimeMetric>,
+{
+    fn inverse(&self, output: Self::OutputTimeMetric) -> TM {
+        self.0.inverse(self.1.inverse(output))
+    }
+}
+
+impl<TM, R0, R1> Rate<TM> for Blend<R0, R1>
+where
+    TM: TimeMetric,
+    R0: Rate<TM>,
+    R1: Rate<TM, OutputTimeMetric = R0::OutputTimeMetric>,
+{
+    type OutputTimeMetric = R0::OutputTimeMetric;
+
+    fn eval(&self, time_metric: TM) -> Self::OutputTimeMetric {
+        let weight = self.2;
+        ((1.0 - weight) * *self.0.eval(time_metric) + weight * *self.1.eval(time_metric)).into()
+    }
+}
+
+// A convex combination of increasing functions is increasing.
+impl<TM, R0, R1> IncreasingRate<TM> for Blend<R0, R1>
+where
+    TM: TimeMetric,
+    R0: IncreasingRate<TM>,
+    R1: IncreasingRate<TM, OutputTimeMetric = R0::OutputTimeMetric>,
+{
+}
+
+impl<R> Rate<NormalizedTimeMetric> for Reverse<R>
+where
+    R: Rate<NormalizedTimeMetric>,
+{
+    type OutputTimeMetric = R::OutputTimeMetric;
+
+    fn eval(&self, time_metric: NormalizedTimeMetric) -> Self::OutputTimeMetric {
+        self.0.eval((1.0 - *time_metric).into())
+    }
+}
+
+impl<R> Rate<NormalizedTimeMetric> for ThereAndBack<R>
+where
+    R: Rate<NormalizedTimeMetric>,
+{
+    type OutputTimeMetric = R::OutputTimeMetric;
+
+    fn eval(&self, time_metric: NormalizedTimeMetric) -> Self::OutputTimeMetric {
+        let t = *time_metric;
+        self.0
+            .eval(if t < 0.5 { 2.0 * t } else { 2.0 - 2.0 * t }.into())
+    }
+}
+
+impl<R> Rate<NormalizedTimeMetric> for Repeat<R>
+where
+    R: Rate<NormalizedTimeMetric>,
+{
+    type OutputTimeMetric = R::OutputTimeMetric;
+
+    // The end of the last repetition is kept at `1` instead of wrapping back to `0`.
+    fn eval(&self, time_metric: NormalizedTimeMetric) -> Self::OutputTimeMetric {
+        let t = *time_metric * self.1 as f32;
+        let t = if t >= self.1 as f32 { 1.0 } else { t.fract() };
+        self.0.eval(t.into())
+    }
+}
+
+impl<R> Rate<NormalizedTimeMetric> for Lag<R>
+where
+    R: Rate<NormalizedTimeMetric>,
+{
+    type OutputTimeMetric = R::OutputTimeMetric;
+
+    fn eval(&self, time_metric: NormalizedTimeMetric) -> Self::OutputTimeMetric {
+        let ratio = self.1;
+        self.0
+            .eval(((*time_metric - ratio) / (1.0 - ratio)).max(0.0).into())
+    }
+}
+
+impl<R> IncreasingRate<NormalizedTimeMetric> for Lag<R>
+where
+    R: IncreasingRate<NormalizedTimeMetric>,
+{
+    fn inverse(&self, output: Self::OutputTimeMetric) -> NormalizedTimeMetric {
+        let ratio = self.1;
+        let time = *self.0.inverse(output);
+        if time <= 0.0 {
+            0.0.into()
+        } else {
+            (ratio + (1.0 - ratio) * time).into()
+        }
+    }
+}
+
+impl<R> Rate<NormalizedTimeMetric> for Clamp<R>
+where
+    R: Rate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric>,
+{
+    type OutputTimeMetric = NormalizedTimeMetric;
+
+    fn eval(&self, time_metric: NormalizedTimeMetric) -> Self::OutputTimeMetric {
+        self.0.eval(time_metric).0.clamp(0.0, 1.0).into()
+    }
+}
+
+impl<R> IncreasingRate<NormalizedTimeMetric> for Clamp<R> where
+    R: IncreasingRate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric>
+{
+}
+
+impl<R> Rate<NormalizedTimeMetric> for Mirror<R>
+where
+    R: Rate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric>,
+{
+    type OutputTimeMetric = NormalizedTimeMetric;
+
+    fn eval(&self, time_metric: NormalizedTimeMetric) -> Self::OutputTimeMetric {
+        (1.0 - *self.0.eval((1.0 - *time_metric).into())).into()
+    }
+}
+
+// The earliest input of a mirrored rate mirrors the latest input of the original one,
+// so flat segments are left to the default bisection.
+impl<R> IncreasingRate<NormalizedTimeMetric> for Mirror<R> where
+    R: IncreasingRate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric>
+{
+}
+
+#[cfg(test)]
+mod rate_tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Quadratic;
+
+    impl Rate<NormalizedTimeMetric> for Quadratic {
+        type OutputTimeMetric = NormalizedTimeMetric;
+
+        fn eval(&self, time_metric: NormalizedTimeMetric) -> Self::OutputTimeMetric {
+            (*time_metric * *time_metric).into()
+        }
+    }
+
+    impl IncreasingRate<NormalizedTimeMetric> for Quadratic {}
+
+    fn eval<R>(rate: &R, t: f32) -> f32
+    where
+        R: Rate<NormalizedTimeMetric>,
+    {
+        *rate.eval(t.into())
+    }
+
+    fn assert_increasing<R>(_: &R)
+    where
+        R: IncreasingRate<NormalizedTimeMetric>,
+    {
+    }
+
+    #[test]
+    fn test_rate_combinators() {
+        assert_eq!(eval(&Quadratic.then(Quadratic), 0.5), 0.0625);
+        assert_eq!(eval(&Quadratic.reverse(), 0.25), 0.5625);
+        assert_eq!(eval(&Quadratic.there_and_back(), 0.75), 0.25);
+        assert_eq!(eval(&Quadratic.repeat(2), 0.75), 0.25);
+        assert_eq!(eval(&Quadratic.repeat(2), 1.0), 1.0);
+        assert_eq!(eval(&Quadratic.mirror(), 0.5), 0.75);
+        assert_eq!(eval(&Quadratic.lag(0.5), 0.25), 0.0);
+        assert_eq!(eval(&Quadratic.lag(0.5), 0.75), 0.25);
+        assert_eq!(eval(&Quadratic.mirror().blend(Quadratic, 0.5), 0.5), 0.5);
+        assert_eq!(eval(&Quadratic.then(Quadratic.reverse()).clamp(), 0.0), 1.0);
+
+        assert_increasing(&Quadratic.then(Quadratic).mirror().lag(0.5).clamp());
+        assert_increasing(&Quadratic.blend(Quadratic.mirror(), 0.25));
+    }
+
+    #[test]
+    fn test_rate_inverse() {
+        fn inverse<R>(rate: &R, output: f32) -> f32
+        where
+            R: IncreasingRate<NormalizedTimeMetric, OutputTimeMetric = NormalizedTimeMetric>,
+        {
+            *rate.inverse(output.into())
+        }
+
+        assert!((inverse(&Quadratic, 0.25) - 0.5).abs() < 1e-6);
+        assert!((inverse(&Quadratic.mirror(), 0.75) - 0.5).abs() < 1e-6);
+        assert!((inverse(&Quadratic.then(Quadratic), 0.0625) - 0.5).abs() < 1e-6);
+        assert!((inverse(&Quadratic.lag(0.5), 0.25) - 0.75).abs() < 1e-6);
+        assert_eq!(inverse(&Quadratic.lag(0.5), 0.0), 0.0);
+        // Outputs beyond `f(1)` are reached by extrapolation.
+        assert!((inverse(&Quadratic, 4.0) - 2.0).abs() < 1e-6);
+    }
+}
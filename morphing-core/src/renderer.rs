@@ -0,0 +1,242 @@
+use super::scene::Time;
+use super::scene::Timeline;
+use super::scene::TimelineResources;
+
+// Matches the color target format of builtin pipelines.
+// Pixels are swizzled into RGBA order on readback.
+pub const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+#[derive(Debug)]
+pub enum RendererError {
+    RequestAdapter(wgpu::RequestAdapterError),
+    RequestDevice(wgpu::RequestDeviceError),
+    BufferAsync(wgpu::BufferAsyncError),
+    Poll(wgpu::PollError),
+}
+
+impl std::fmt::Display for RendererError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RequestAdapter(error) => write!(f, "Failed to request adapter: {error}"),
+            Self::RequestDevice(error) => write!(f, "Failed to request device: {error}"),
+            Self::BufferAsync(error) => write!(f, "Failed to map readback buffer: {error}"),
+            Self::Poll(error) => write!(f, "Failed to poll device: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RendererError {}
+
+// Offscreen renderer drawing timeline frames into a texture,
+// which is then copied back to the host as tightly packed RGBA8 pixels.
+pub struct Renderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+    readback_buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    background_color: wgpu::Color,
+}
+
+impl Renderer {
+    pub fn new(
+        width: u32,
+        height: u32,
+        background_color: wgpu::Color,
+    ) -> Result<Self, RendererError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        }))
+        .map_err(RendererError::RequestAdapter)?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+                .map_err(RendererError::RequestDevice)?;
+        Ok(Self::from_device(
+            device,
+            queue,
+            width,
+            height,
+            background_color,
+        ))
+    }
+
+    pub fn from_device(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        width: u32,
+        height: u32,
+        background_color: wgpu::Color,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let padded_bytes_per_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            device,
+            queue,
+            texture,
+            texture_view,
+            readback_buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            background_color,
+        }
+    }
+
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    // Returns `width * height * 4` bytes of RGBA8 pixels in row-major order.
+    pub fn render(
+        &self,
+        timeline: &Timeline,
+        resources: &mut TimelineResources,
+        time: Time,
+    ) -> Result<Vec<u8>, RendererError> {
+        timeline.prepare(time, resources, &self.device, &self.queue);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.texture_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.background_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            timeline.render(time, resources, &mut render_pass);
+        }
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.readback_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            self.texture.size(),
+        );
+        let submission_index = self.queue.submit(Some(encoder.finish()));
+
+        let buffer_slice = self.readback_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            sender.send(result).unwrap();
+        });
+        self.device
+            .poll(wgpu::PollType::Wait {
+                submission_index: Some(submission_index),
+                timeout: None,
+            })
+            .map_err(RendererError::Poll)?;
+        receiver
+            .recv()
+            .unwrap()
+            .map_err(RendererError::BufferAsync)?;
+
+        let mut pixels = Vec::with_capacity((self.width * self.height * 4) as usize);
+        {
+            let mapped_range = buffer_slice.get_mapped_range();
+            for row in mapped_range
+                .chunks_exact(self.padded_bytes_per_row as usize)
+                .take(self.height as usize)
+            {
+                for bgra in row[..(self.width * 4) as usize].chunks_exact(4) {
+                    pixels.extend([bgra[2], bgra[1], bgra[0], bgra[3]]);
+                }
+            }
+        }
+        self.readback_buffer.unmap();
+        Ok(pixels)
+    }
+}
+
+#[cfg(test)]
+mod renderer_tests {
+    use std::io::Write;
+
+    use super::super::scene::Supervisor;
+    use super::*;
+
+    #[test]
+    fn test_background() {
+        // Rows of 70 pixels are padded in the readback buffer, which is 64-pixel aligned.
+        let (width, height) = (70, 3);
+        // Linear 0.21586 is 128 in sRGB, while alpha is stored as is.
+        let background_color = wgpu::Color {
+            r: 1.0,
+            g: 0.21586,
+            b: 0.0,
+            a: 0.6,
+        };
+        let renderer = match Renderer::new(width, height, background_color) {
+            Ok(renderer) => renderer,
+            Err(error @ RendererError::RequestAdapter(_)) => {
+                // Written past the output capture, so that it shows up even though the test passes.
+                writeln!(std::io::stderr(), "\nSKIPPED `test_background`: {error}").unwrap();
+                return;
+            }
+            Err(error) => panic!("{error}"),
+        };
+        let timeline = Supervisor::new(()).into_timeline();
+        let pixels = renderer
+            .render(&timeline, &mut TimelineResources::default(), 0.0)
+            .unwrap();
+        assert_eq!(pixels.len(), (width * height * 4) as usize);
+        for pixel in pixels.chunks_exact(4) {
+            assert!(
+                pixel
+                    .iter()
+                    .zip([255, 128, 0, 153])
+                    .all(|(&channel, expected)| channel.abs_diff(expected) <= 1),
+                "{pixel:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::Path;
+use std::process::Child;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use super::link::SceneError;
+use super::link::call_entrypoint;
+use super::link::catch_unwind;
+use super::scene::Time;
+
+// Any binary calling `runner_mode` first thing in `main` may be spawned as a runner:
+// - `<exe> --morphing-runner run <library>` runs every scene of the chapter, printing `LineOutput` records;
+// - `<exe> --morphing-runner work <library> <report> [scene]` is the worker behind each record.
+const RUNNER_ARG: &str = "--morphing-runner";
+
+// A worker taking longer, e.g. stuck in a loop, is killed and reported as crashed.
+const WORKER_TIMEOUT: Duration = Duration::from_secs(120);
+
+// One JSON object per line.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineOutput {
+    // Loading the chapter, resulting in the names of its scenes.
+    Project(RedirectedOutput<Vec<String>>),
+    Scene(String, RedirectedOutput<SceneData>),
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct RedirectedOutput<T> {
+    pub stdout_lines: Vec<String>,
+    pub stderr_lines: Vec<String>,
+    pub result: Result<T, Failure>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct SceneData {
+    pub duration: Time,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Failure {
+    Error {
+        message: String,
+    },
+    Panic {
+        message: String,
+        location: Option<String>,
+    },
+    // The worker could not be run, or died without reporting, e.g. on abort or a signal.
+    Crash {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error { message } => write!(f, "{message}"),
+            Self::Panic {
+                message,
+                location: Some(location),
+            } => write!(f, "Panicked at {location}: {message}"),
+            Self::Panic {
+                message,
+                location: None,
+            } => write!(f, "Panicked: {message}"),
+            Self::Crash { message } => write!(f, "Crashed: {message}"),
+        }
+    }
+}
+
+impl From<SceneError> for Failure {
+    fn from(error: SceneError) -> Self {
+        match error {
+            SceneError::Config(error) => Self::Error {
+                message: error.to_string(),
+            },
+            SceneError::Error(message) => Self::Error { message },
+            SceneError::Panic { message, location } => Self::Panic { message, location },
+        }
+    }
+}
+
+pub fn read_and_deserialize(line: &str) -> serde_json::Result<LineOutput> {
+    serde_json::from_str(line)
+}
+
+// Does not return if the process was spawned as a runner.
+pub fn runner_mode() {
+    let mut args = std::env::args_os().skip(1);
+    if args.next().is_none_or(|arg| arg != RUNNER_ARG) {
+        return;
+    }
+    let args: Vec<OsString> = args.collect();
+    let code = match args.iter().map(OsString::as_os_str).collect::<Vec<_>>()[..] {
+        [mode, library] if mode == "run" => {
+            for line_output in run_isolated(Path::new(library)) {
+                println!(
+                    "{}",
+                    serde_json::to_string(&line_output).expect("Failed to serialize record")
+                );
+            }
+            0
+        }
+        [mode, library, report] if mode == "work" => work(library, report, None),
+        [mode, library, report, scene] if mode == "work" => work(library, report, Some(scene)),
+        _ => {
+            eprintln!("Usage: {RUNNER_ARG} run <library>");
+            2
+        }
+    };
+    std::process::exit(code);
+}
+
+// Runs every scene of the chapter in a separate worker process, so that nothing
+// a scene does may take the caller down. The current executable must call `runner_mode`.
+pub fn run_isolated(library: &Path) -> impl Iterator<Item = LineOutput> + '_ {
+    let project = spawn_worker::<Vec<String>>(library, None);
+    let names = project.result.clone().unwrap_or_default();
+    std::iter::once(LineOutput::Project(project)).chain(names.into_iter().map(move |name| {
+        let scene = spawn_worker(library, Some(&name));
+        LineOutput::Scene(name, scene)
+    }))
+}
+
+// Output is captured through pipes, while the result is reported through a file
+// so that it cannot be confused with anything the scene prints.
+fn spawn_worker<T>(library: &Path, scene: Option<&str>) -> RedirectedOutput<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    static REPORT_COUNT: AtomicUsize = AtomicUsize::new(0);
+    let report_path = std::env::temp_dir().join("morphing").join(format!(
+        "report-{}-{}.json",
+        std::process::id(),
+        REPORT_COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let child = std::fs::create_dir_all(report_path.parent().unwrap()).and_then(|()| {
+        std::env::current_exe().and_then(|exe| {
+            Command::new(exe)
+                .arg(RUNNER_ARG)
+                .arg("work")
+                .arg(library)
+                .arg(&report_path)
+                .args(scene)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+        })
+    });
+    let output = match child.and_then(wait_with_timeout) {
+        Ok(output) => output,
+        Err(error) => {
+            return RedirectedOutput {
+                stdout_lines: Vec::new(),
+                stderr_lines: Vec::new(),
+                result: Err(Failure::Crash {
+                    message: error.to_string(),
+                }),
+            };
+        }
+    };
+    let report = std::fs::read_to_string(&report_path);
+    std::fs::remove_file(&report_path).ok();
+    let result = match output.status {
+        Some(status) => report
+            .ok()
+            .and_then(|report| serde_json::from_str(&report).ok())
+            .unwrap_or_else(|| {
+                Err(Failure::Crash {
+                    message: status.to_string(),
+                })
+            }),
+        None => Err(Failure::Crash {
+            message: format!("Killed after {} s", WORKER_TIMEOUT.as_secs()),
+        }),
+    };
+    let lines = |bytes: &[u8]| {
+        String::from_utf8_lossy(bytes)
+            .lines()
+            .map(String::from)
+            .collect()
+    };
+    RedirectedOutput {
+        stdout_lines: lines(&output.stdout),
+        stderr_lines: lines(&output.stderr),
+        result,
+    }
+}
+
+struct WorkerOutput {
+    // `None` if the worker was killed on timeout.
+    status: Option<ExitStatus>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+// Pipes are drained on separate threads, so that a chatty worker cannot block on a full pipe.
+fn wait_with_timeout(mut child: Child) -> std::io::Result<WorkerOutput> {
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut bytes).ok();
+            }
+            bytes
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let deadline = Instant::now() + WORKER_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    Ok(WorkerOutput {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn work(library: &OsStr, report: &OsStr, scene: Option<&OsStr>) -> i32 {
+    // The payload is reported instead of being printed to stderr.
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = catch_unwind(|| -> Result<serde_json::Value, Failure> {
+        let chapter = call_entrypoint(library).map_err(|error| Failure::Error {
+            message: error.to_string(),
+        })?;
+        match scene {
+            None => {
+                let mut names: Vec<&String> = chapter.scenes().keys().collect();
+                names.sort();
+                Ok(serde_json::json!(names))
+            }
+            Some(scene) => {
+                let scene = scene.to_string_lossy();
+                let entry = chapter
+                    .scenes()
+                    .get(&*scene)
+                    .ok_or_else(|| Failure::Error {
+                        message: format!("No scene named `{scene}`"),
+                    })?;
+                // Panics within the scene are caught in the chapter library.
+                let timeline = entry.timeline(chapter.config_layers())?;
+                Ok(serde_json::json!(SceneData {
+                    duration: timeline.duration(),
+                }))
+            }
+        }
+    })
+    .unwrap_or_else(|error| Err(error.into()));
+    let succeeded = result.is_ok();
+    match serde_json::to_string(&result)
+        .map_err(std::io::Error::from)
+        .and_then(|result| std::fs::write(report, result))
+    {
+        Ok(()) if succeeded => 0,
+        Ok(()) => 1,
+        Err(error) => {
+            eprintln!("Failed to write runner report: {error}");
+            1
+        }
+    }
+}
@@ -1,32 +1,657 @@
-type Duration = std::time::Duration;
-type Clock = std::time::SystemTime;
+use std::any::Any;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::IndexMut;
+use std::ops::Range;
 
-use std::sync::Arc;
+use super::interpolate::Interpolate;
 
-type Signal = f32;
-type Resource = (bool,);
-type RenderContext = wgpu::RenderPass<'static>;
+pub type Time = f32;
 
 pub trait Lifecycle: 'static + Send + Sync {
-    // type Signal = f32;
-    // type Resource = (bool,);
+    type Signal: From<Time>;
+    type Resource: 'static + Send + Sync;
 
-    fn setup(&self) -> Resource;
-    fn prepare(&self, signal: Signal, resource: &mut Resource);
-    fn render(&self, resource: &Resource);
+    fn setup(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Self::Resource;
+    fn prepare(
+        &self,
+        signal: Self::Signal,
+        resource: &mut Self::Resource,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    );
+    fn render(&self, resource: &Self::Resource, render_pass: &mut wgpu::RenderPass<'_>);
+}
+
+pub type ErasedResource = Box<dyn Any + Send + Sync>;
+
+// Object-safe counterpart of `Lifecycle`, so that lifecycles with different
+// signal and resource types can be stored together as `Box<dyn ErasedLifecycle>`.
+// Resources are type-erased and downcast back on every call.
+pub trait ErasedLifecycle: 'static + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn erased_setup(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> ErasedResource;
+    fn erased_prepare(
+        &self,
+        time: Time,
+        resource: &mut ErasedResource,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    );
+    fn erased_render(&self, resource: &ErasedResource, render_pass: &mut wgpu::RenderPass<'_>);
+}
+
+impl<L> ErasedLifecycle for L
+where
+    L: Lifecycle,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn erased_setup(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> ErasedResource {
+        Box::new(self.setup(device, queue))
+    }
+
+    fn erased_prepare(
+        &self,
+        time: Time,
+        resource: &mut ErasedResource,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        self.prepare(
+            time.into(),
+            resource
+                .downcast_mut()
+                .expect("Resource does not match the lifecycle"),
+            device,
+            queue,
+        );
+    }
+
+    fn erased_render(&self, resource: &ErasedResource, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.render(
+            resource
+                .downcast_ref()
+                .expect("Resource does not match the lifecycle"),
+            render_pass,
+        );
+    }
+}
+
+pub trait MobjectBuilder<C> {
+    type Instantiation: Lifecycle;
+
+    fn instantiate(self, config: &C) -> Self::Instantiation;
+}
+
+// Spawned lifecycles are signaled with the seconds elapsed since they are spawned,
+// while played animations are signaled with their progress normalized into `[0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SignalMetric {
+    Denormalized,
+    Normalized,
+}
+
+struct TimelineEntry {
+    time_interval: Range<Time>,
+    signal_metric: SignalMetric,
+    // Seconds a respawned mobject had already been alive, so that its denormalized signal
+    // keeps counting from the original spawn.
+    signal_offset: Time,
+    lifecycle: Box<dyn ErasedLifecycle>,
+}
+
+impl TimelineEntry {
+    fn signal(&self, time: Time) -> Time {
+        let elapsed = time - self.time_interval.start;
+        match self.signal_metric {
+            SignalMetric::Denormalized => self.signal_offset + elapsed,
+            SignalMetric::Normalized => {
+                let span = self.time_interval.end - self.time_interval.start;
+                if span > 0.0 { elapsed / span } else { 1.0 }
+            }
+        }
+    }
 }
 
 pub struct Supervisor<C> {
-    time: f32,
-    lifecycles: Vec<Box<dyn Lifecycle>>,
+    time: Cell<Time>,
+    entries: RefCell<Vec<TimelineEntry>>,
     config: C,
 }
 
 impl<C> Supervisor<C> {
-    fn with<L>(&mut self, lifecycle: L)
+    pub fn new(config: C) -> Self {
+        Self {
+            time: Cell::new(0.0),
+            entries: RefCell::new(Vec::new()),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &C {
+        &self.config
+    }
+
+    pub fn time(&self) -> Time {
+        self.time.get()
+    }
+
+    pub fn duration(&self) -> Time {
+        self.entries
+            .borrow()
+            .iter()
+            .map(|entry| entry.time_interval.end)
+            .filter(|end| end.is_finite())
+            .fold(self.time(), f32::max)
+    }
+
+    pub fn wait(&self, seconds: Time) {
+        assert!(
+            seconds.is_finite() && seconds >= 0.0,
+            "Cannot wait for negative or infinite time: {seconds}"
+        );
+        self.time.set(self.time() + seconds);
+    }
+
+    pub fn spawn<B>(&self, builder: B) -> Handle<'_, C, B::Instantiation>
+    where
+        B: MobjectBuilder<C>,
+    {
+        let index = self.push(
+            self.time()..f32::INFINITY,
+            SignalMetric::Denormalized,
+            0.0,
+            builder.instantiate(&self.config),
+        );
+        Handle {
+            supervisor: self,
+            index,
+            mobject: PhantomData,
+        }
+    }
+
+    // Replaces the field at `path` of the spawned mobject from now on.
+    pub fn set<M, P>(&self, handle: &mut Handle<'_, C, M>, path: P, value: M::Output)
+    where
+        M: Lifecycle + Clone + IndexMut<P>,
+        M::Output: Sized,
+    {
+        let mut mobject = self.mobject(handle);
+        mobject[path] = value;
+        self.respawn(handle, mobject);
+    }
+
+    // Interpolates the field at `path` of the spawned mobject from its current value to `to`.
+    // The mobject is respawned with the field set to `to` when the animation finishes.
+    pub fn animate<M, P, T>(&self, handle: &mut Handle<'_, C, M>, path: P, to: T, duration: Time)
+    where
+        M: Lifecycle + Clone + IndexMut<P, Output = T>,
+        P: 'static + Clone + Send + Sync,
+        T: 'static + Clone + Interpolate + Send + Sync,
+    {
+        let mobject = self.mobject(handle);
+        let spawned_for = self.spawned_for(handle);
+        self.despawn(handle.index);
+        self.play(
+            FieldAnimation {
+                from: mobject[path.clone()].clone(),
+                to: to.clone(),
+                mobject,
+                path: path.clone(),
+                spawned_for,
+                duration,
+            },
+            duration,
+        );
+        self.set(handle, path, to);
+    }
+
+    pub fn play<L>(&self, animation: L, duration: Time)
+    where
+        L: Lifecycle,
+    {
+        assert!(
+            duration.is_finite() && duration >= 0.0,
+            "Cannot play for negative or infinite time: {duration}"
+        );
+        let time = self.time();
+        self.push(
+            time..time + duration,
+            SignalMetric::Normalized,
+            0.0,
+            animation,
+        );
+        self.wait(duration);
+    }
+
+    // Keeps the lifecycle alive from now on until the end of the scene.
+    pub fn with<L>(&self, lifecycle: L)
+    where
+        L: Lifecycle,
+    {
+        self.push(
+            self.time()..f32::INFINITY,
+            SignalMetric::Denormalized,
+            0.0,
+            lifecycle,
+        );
+    }
+
+    pub fn into_timeline(self) -> Timeline {
+        let duration = self.duration();
+        Timeline {
+            duration,
+            entries: self
+                .entries
+                .into_inner()
+                .into_iter()
+                .map(|mut entry| {
+                    entry.time_interval.end = entry.time_interval.end.min(duration);
+                    entry
+                })
+                .collect(),
+        }
+    }
+
+    fn push<L>(
+        &self,
+        time_interval: Range<Time>,
+        signal_metric: SignalMetric,
+        signal_offset: Time,
+        lifecycle: L,
+    ) -> usize
     where
         L: Lifecycle,
     {
-        self.lifecycles.push(Box::new(lifecycle))
+        let mut entries = self.entries.borrow_mut();
+        entries.push(TimelineEntry {
+            time_interval,
+            signal_metric,
+            signal_offset,
+            lifecycle: Box::new(lifecycle),
+        });
+        entries.len() - 1
+    }
+
+    // The current state of a spawned mobject.
+    fn mobject<M>(&self, handle: &Handle<'_, C, M>) -> M
+    where
+        M: Lifecycle + Clone,
+    {
+        self.entries.borrow()[handle.index]
+            .lifecycle
+            .as_any()
+            .downcast_ref::<M>()
+            .expect("Handle does not match the mobject")
+            .clone()
+    }
+
+    // Seconds elapsed since the mobject was first spawned, carried across respawns.
+    // Measured from the entry of the handle, which may have been despawned in the meantime.
+    fn spawned_for<M>(&self, handle: &Handle<'_, C, M>) -> Time {
+        self.entries.borrow()[handle.index].signal(self.time())
+    }
+
+    // The respawned mobject keeps being signaled with the seconds elapsed since it was first spawned.
+    fn respawn<M>(&self, handle: &mut Handle<'_, C, M>, mobject: M)
+    where
+        M: Lifecycle,
+    {
+        let spawned_for = self.spawned_for(handle);
+        self.despawn(handle.index);
+        handle.index = self.push(
+            self.time()..f32::INFINITY,
+            SignalMetric::Denormalized,
+            spawned_for,
+            mobject,
+        );
+    }
+
+    fn despawn(&self, index: usize) {
+        let time = self.time();
+        let mut entries = self.entries.borrow_mut();
+        let time_interval = &mut entries[index].time_interval;
+        time_interval.end = time_interval.end.min(time);
+    }
+}
+
+// Dropping the handle despawns the mobject at the current time of the supervisor.
+pub struct Handle<'sv, C, M> {
+    supervisor: &'sv Supervisor<C>,
+    index: usize,
+    mobject: PhantomData<fn() -> M>,
+}
+
+impl<C, M> Handle<'_, C, M> {
+    pub fn despawn(self) {}
+}
+
+impl<C, M> Drop for Handle<'_, C, M> {
+    fn drop(&mut self) {
+        self.supervisor.despawn(self.index);
+    }
+}
+
+// Played by `Supervisor::animate`. The resource is set up once, and the interpolated mobject
+// is prepared with it on every frame, signaled with the seconds elapsed since the mobject
+// was spawned. Mobjects are expected to bring their resource up to date in `prepare`,
+// so rendering is delegated to the mobject as it was before the animation.
+struct FieldAnimation<M, P, T> {
+    mobject: M,
+    path: P,
+    from: T,
+    to: T,
+    // Seconds the mobject had been alive when the animation started.
+    spawned_for: Time,
+    duration: Time,
+}
+
+impl<M, P, T> FieldAnimation<M, P, T>
+where
+    M: Clone + IndexMut<P, Output = T>,
+    P: Clone,
+    T: Interpolate,
+{
+    fn mobject_at(&self, alpha: f32) -> M {
+        let mut mobject = self.mobject.clone();
+        mobject[self.path.clone()] = self.from.interpolate(&self.to, alpha);
+        mobject
+    }
+
+    // The denormalized signal of the mobject, continuing the one it had before the animation.
+    fn mobject_signal(&self, alpha: f32) -> Time {
+        self.spawned_for + alpha * self.duration
+    }
+}
+
+impl<M, P, T> Lifecycle for FieldAnimation<M, P, T>
+where
+    M: Lifecycle + Clone + IndexMut<P, Output = T>,
+    P: 'static + Clone + Send + Sync,
+    T: 'static + Interpolate + Send + Sync,
+{
+    type Signal = Time;
+    type Resource = M::Resource;
+
+    fn setup(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Self::Resource {
+        self.mobject_at(0.0).setup(device, queue)
+    }
+
+    fn prepare(
+        &self,
+        signal: Self::Signal,
+        resource: &mut Self::Resource,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let alpha = signal.clamp(0.0, 1.0);
+        self.mobject_at(alpha)
+            .prepare(self.mobject_signal(alpha).into(), resource, device, queue);
+    }
+
+    fn render(&self, resource: &Self::Resource, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.mobject.render(resource, render_pass);
+    }
+}
+
+pub struct Timeline {
+    duration: Time,
+    entries: Vec<TimelineEntry>,
+}
+
+impl Timeline {
+    pub fn duration(&self) -> Time {
+        self.duration
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Yields `(index, signal, lifecycle)` for every lifecycle alive at `time`,
+    // i.e. whose time interval `[start, end)` contains `time`.
+    // The index is stable and can be used to key cached resources.
+    pub fn alive(&self, time: Time) -> impl Iterator<Item = (usize, Time, &dyn ErasedLifecycle)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(move |(_, entry)| entry.time_interval.contains(&time))
+            .map(move |(index, entry)| (index, entry.signal(time), entry.lifecycle.as_ref()))
+    }
+
+    pub fn prepare(
+        &self,
+        time: Time,
+        resources: &mut TimelineResources,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        for (index, signal, lifecycle) in self.alive(time) {
+            let resource = resources
+                .0
+                .entry(index)
+                .or_insert_with(|| lifecycle.erased_setup(device, queue));
+            lifecycle.erased_prepare(signal, resource, device, queue);
+        }
+    }
+
+    // Lifecycles not yet prepared are skipped.
+    pub fn render(
+        &self,
+        time: Time,
+        resources: &TimelineResources,
+        render_pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        for (index, _, lifecycle) in self.alive(time) {
+            if let Some(resource) = resources.0.get(&index) {
+                lifecycle.erased_render(resource, render_pass);
+            }
+        }
+    }
+}
+
+// Resources set up for the entries of a single timeline, keyed by entry index.
+#[derive(Default)]
+pub struct TimelineResources(HashMap<usize, ErasedResource>);
+
+#[cfg(test)]
+mod scene_tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Probe;
+
+    impl Lifecycle for Probe {
+        type Signal = Time;
+        type Resource = ();
+
+        fn setup(&self, _device: &wgpu::Device, _queue: &wgpu::Queue) -> Self::Resource {}
+
+        fn prepare(
+            &self,
+            _signal: Self::Signal,
+            _resource: &mut Self::Resource,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) {
+        }
+
+        fn render(&self, _resource: &Self::Resource, _render_pass: &mut wgpu::RenderPass<'_>) {}
+    }
+
+    impl MobjectBuilder<()> for Probe {
+        type Instantiation = Self;
+
+        fn instantiate(self, _config: &()) -> Self::Instantiation {
+            self
+        }
+    }
+
+    #[derive(Clone, crate::FieldIndex)]
+    struct Dot {
+        radius: f32,
+    }
+
+    impl Lifecycle for Dot {
+        type Signal = Time;
+        type Resource = ();
+
+        fn setup(&self, _device: &wgpu::Device, _queue: &wgpu::Queue) -> Self::Resource {}
+
+        fn prepare(
+            &self,
+            _signal: Self::Signal,
+            _resource: &mut Self::Resource,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) {
+        }
+
+        fn render(&self, _resource: &Self::Resource, _render_pass: &mut wgpu::RenderPass<'_>) {}
+    }
+
+    impl MobjectBuilder<()> for Dot {
+        type Instantiation = Self;
+
+        fn instantiate(self, _config: &()) -> Self::Instantiation {
+            self
+        }
+    }
+
+    fn radius(lifecycle: &dyn ErasedLifecycle) -> f32 {
+        lifecycle.as_any().downcast_ref::<Dot>().unwrap().radius
+    }
+
+    // The path is only taken to name the type of the animation.
+    fn field_animation<P: 'static>(
+        _path: P,
+        lifecycle: &dyn ErasedLifecycle,
+    ) -> &FieldAnimation<Dot, P, f32> {
+        lifecycle.as_any().downcast_ref().unwrap()
+    }
+
+    fn intervals(timeline: &Timeline) -> Vec<Range<Time>> {
+        timeline
+            .entries
+            .iter()
+            .map(|entry| entry.time_interval.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_timeline_intervals() {
+        let supervisor = Supervisor::new(());
+        supervisor.wait(1.0);
+        let handle = supervisor.spawn(Probe);
+        supervisor.play(Probe, 2.0);
+        supervisor.wait(0.5);
+        supervisor.with(Probe);
+        supervisor.wait(-0.0);
+        drop(handle);
+        let timeline = supervisor.into_timeline();
+        assert_eq!(timeline.duration(), 3.5);
+        // Unbounded intervals end with the scene.
+        assert_eq!(intervals(&timeline), [1.0..3.5, 1.0..3.0, 3.5..3.5]);
+    }
+
+    #[test]
+    fn test_despawn_on_drop() {
+        let supervisor = Supervisor::new(());
+        let first = supervisor.spawn(Probe);
+        let second = supervisor.spawn(Probe);
+        supervisor.wait(1.0);
+        first.despawn();
+        supervisor.wait(1.0);
+        drop(second);
+        supervisor.wait(1.0);
+        assert_eq!(intervals(&supervisor.into_timeline()), [0.0..1.0, 0.0..2.0]);
+    }
+
+    #[test]
+    fn test_alive() {
+        let supervisor = Supervisor::new(());
+        let handle = supervisor.spawn(Probe);
+        supervisor.wait(1.0);
+        supervisor.play(Probe, 2.0);
+        drop(handle);
+        let timeline = supervisor.into_timeline();
+        let alive = |time| {
+            timeline
+                .alive(time)
+                .map(|(index, signal, _)| (index, signal))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(alive(0.5), [(0, 0.5)]);
+        assert_eq!(alive(2.0), [(0, 2.0), (1, 0.5)]);
+        // Intervals are half-open, so nothing is alive at the very end.
+        assert_eq!(alive(3.0), []);
+    }
+
+    #[test]
+    fn test_set() {
+        let supervisor = Supervisor::new(());
+        let mut handle = supervisor.spawn(Dot { radius: 1.0 });
+        supervisor.wait(1.0);
+        supervisor.set(&mut handle, crate::fp!(radius), 2.0);
+        supervisor.wait(1.0);
+        drop(handle);
+        let timeline = supervisor.into_timeline();
+        assert_eq!(intervals(&timeline), [0.0..1.0, 1.0..2.0]);
+        // Signaled with the seconds elapsed since first spawned, across the respawn.
+        let signals = |time| {
+            timeline
+                .alive(time)
+                .map(|(_, signal, lifecycle)| (signal, radius(lifecycle)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(signals(0.5), [(0.5, 1.0)]);
+        assert_eq!(signals(1.0), [(1.0, 2.0)]);
+        assert_eq!(signals(1.5), [(1.5, 2.0)]);
+    }
+
+    #[test]
+    fn test_animate() {
+        let supervisor = Supervisor::new(());
+        let mut handle = supervisor.spawn(Dot { radius: 1.0 });
+        supervisor.wait(1.0);
+        supervisor.set(&mut handle, crate::fp!(radius), 2.0);
+        supervisor.wait(1.0);
+        supervisor.animate(&mut handle, crate::fp!(radius), 3.0, 2.0);
+        supervisor.wait(1.0);
+        drop(handle);
+        let timeline = supervisor.into_timeline();
+        assert_eq!(timeline.duration(), 5.0);
+        assert_eq!(
+            intervals(&timeline),
+            [0.0..1.0, 1.0..2.0, 2.0..4.0, 4.0..5.0]
+        );
+
+        // The signal of the mobject runs on through the animation, measured from the first spawn.
+        let (index, signal, lifecycle) = timeline.alive(3.0).next().unwrap();
+        assert_eq!((index, signal), (2, 0.5));
+        let animation = field_animation(crate::fp!(radius), lifecycle);
+        assert_eq!(animation.mobject_at(signal).radius, 2.5);
+        assert_eq!(animation.mobject_signal(0.0), 2.0);
+        assert_eq!(animation.mobject_signal(signal), 3.0);
+        assert_eq!(animation.mobject_signal(1.0), 4.0);
+
+        let (index, signal, lifecycle) = timeline.alive(4.0).next().unwrap();
+        assert_eq!((index, signal), (3, 4.0));
+        assert_eq!(radius(lifecycle), 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot wait")]
+    fn test_wait_rejects_nan() {
+        Supervisor::new(()).wait(Time::NAN);
     }
 }
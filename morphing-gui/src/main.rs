@@ -1,9 +1,26 @@
-// use morphing_core::{};
+mod project;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use morphing_builtins::configs::general::GeneralConfig;
+use morphing_core::ChapterSymbol;
+use morphing_core::Failure;
+use morphing_core::SceneData;
+use morphing_core::SceneError;
+use morphing_core::Timeline;
+use morphing_core::TimelineResources;
+
+use project::LibraryCopy;
+use project::Project;
+use project::Reload;
 
 struct SceneSlice {
     time: f32,
-    lifecycles: Arc<[Box<dyn Lifecycle>]>,
+    timeline: Arc<Timeline>,
 }
 
 impl egui_wgpu::CallbackTrait for SceneSlice {
@@ -11,25 +28,26 @@ impl egui_wgpu::CallbackTrait for SceneSlice {
         &self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        screen_descriptor: &egui_wgpu::ScreenDescriptor,
-        egui_encoder: &mut wgpu::CommandEncoder,
+        _screen_descriptor: &egui_wgpu::ScreenDescriptor,
+        _egui_encoder: &mut wgpu::CommandEncoder,
         callback_resources: &mut egui_wgpu::CallbackResources,
     ) -> Vec<wgpu::CommandBuffer> {
-        // let (lifecycles, duration) = self;
-        // for lifecycle in lifecycles.iter() {
-        //     let resource_hashmap: &mut HashMap<TriangleLifecycle, Resource> =
-        //         callback_resources.entry().or_insert_with(HashMap::new);
-        //     let resource = resource_hashmap.entry()
-        // }
+        let resources = callback_resources
+            .entry::<TimelineResources>()
+            .or_insert_with(TimelineResources::default);
+        self.timeline.prepare(self.time, resources, device, queue);
         Vec::new()
     }
 
     fn paint(
         &self,
-        info: epaint::PaintCallbackInfo,
+        _info: epaint::PaintCallbackInfo,
         render_pass: &mut wgpu::RenderPass<'static>,
         callback_resources: &egui_wgpu::CallbackResources,
     ) {
+        if let Some(resources) = callback_resources.get::<TimelineResources>() {
+            self.timeline.render(self.time, resources, render_pass);
+        }
 
         // let code = /*toml*/ r#"
         //     [a.b.c]
@@ -45,53 +63,221 @@ impl egui_wgpu::CallbackTrait for SceneSlice {
     }
 }
 
-pub struct MyApp {
+// The scene on display, with its playhead driven by `clock`.
+struct ActiveScene {
+    name: String,
+    clock: Instant,
+    timeline: Arc<Timeline>,
+}
+
+impl ActiveScene {
+    fn new(chapter_symbol: &ChapterSymbol, name: String, time: f32) -> Result<Self, SceneError> {
+        let timeline = chapter_symbol.scenes()[&name].timeline(chapter_symbol.config_layers())?;
+        Ok(Self {
+            name,
+            clock: Instant::now() - Duration::from_secs_f32(time),
+            timeline: timeline.into(),
+        })
+    }
+
+    fn time(&self) -> f32 {
+        self.clock
+            .elapsed()
+            .as_secs_f32()
+            .min(self.timeline.duration())
+    }
+}
+
+// Fields are dropped in order, so everything built from the chapter goes before its library.
+struct LoadedChapter {
+    scene: Option<ActiveScene>,
+    general_config: GeneralConfig,
+    scene_results: HashMap<String, Result<SceneData, Failure>>,
     chapter_symbol: ChapterSymbol,
-    clock: Clock,
-    lifecycles: Arc<[Box<dyn Lifecycle>]>,
+    _library_copy: LibraryCopy,
 }
 
-impl MyApp {
-    fn new(cc: &eframe::CreationContext<'_>, chapter_path: &str) -> Self {
-        let mut supervisor = Supervisor {
-            time: 0.0,
-            lifecycles: Vec::new(),
-            config: MyConfig {},
+impl LoadedChapter {
+    // Keeps the previous scene and playhead time if the scene still exists.
+    // Scenes that failed in the runner are never run here.
+    fn new(reload: Reload, previous: Option<(&str, f32)>) -> Result<Self, SceneError> {
+        let Reload {
+            chapter_symbol,
+            scene_results,
+            library_copy,
+        } = reload;
+        let general_config = GeneralConfig::from_layers(&chapter_symbol.config_layers())?;
+        let runnable = |name: &str| scene_results.get(name).is_some_and(Result::is_ok);
+        let scene = match previous.filter(|(name, _)| runnable(name)) {
+            Some((name, time)) => Some(ActiveScene::new(&chapter_symbol, name.into(), time)?),
+            None => scene_results
+                .keys()
+                .filter(|name| runnable(name))
+                .min()
+                .cloned()
+                .map(|name| ActiveScene::new(&chapter_symbol, name, 0.0))
+                .transpose()?,
         };
-        my_scene(&mut supervisor);
-        Self {
-            chapter_symbol: call_entrypoint(chapter_path),
-            clock: Clock::now(),
-            lifecycles: supervisor.lifecycles.into(),
+        Ok(Self {
+            scene,
+            general_config,
+            scene_results,
+            chapter_symbol,
+            _library_copy: library_copy,
+        })
+    }
+
+    // Returns whether another scene was picked.
+    fn scene_picker(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut scene_results: Vec<_> = self.scene_results.iter().collect();
+        scene_results.sort_by_key(|(name, _)| *name);
+        let active_name = self.scene.as_ref().map(|scene| scene.name.as_str());
+        let mut selected = None;
+        egui::ComboBox::from_id_salt("scene")
+            .selected_text(active_name.unwrap_or_default())
+            .show_ui(ui, |ui| {
+                for (name, result) in scene_results {
+                    let label = ui.add_enabled(
+                        result.is_ok(),
+                        egui::Button::selectable(active_name == Some(name.as_str()), name),
+                    );
+                    if let Err(failure) = result {
+                        label.on_disabled_hover_text(failure.to_string());
+                    } else if label.clicked() {
+                        selected = Some(name.clone());
+                    }
+                }
+            });
+        let Some(name) = selected else {
+            return false;
+        };
+        match ActiveScene::new(&self.chapter_symbol, name, 0.0) {
+            Ok(scene) => {
+                self.scene = Some(scene);
+                true
+            }
+            Err(error) => {
+                eprintln!("{error}");
+                false
+            }
         }
     }
 
-    fn custom_painting(&mut self, ui: &mut egui::Ui) {
-        let (rect, _response) =
-            ui.allocate_exact_size(egui::Vec2::new(960.0, 540.0), egui::Sense::drag());
+    fn custom_painting(&self, ui: &mut egui::Ui) {
+        let Some(scene) = &self.scene else {
+            return;
+        };
+        // Fits the viewport into the available space, keeping the configured aspect ratio.
+        let available_size = ui.available_size();
+        let aspect_ratio = self.general_config.resolution.aspect_ratio();
+        let size = if available_size.x > available_size.y * aspect_ratio {
+            egui::Vec2::new(available_size.y * aspect_ratio, available_size.y)
+        } else {
+            egui::Vec2::new(available_size.x, available_size.x / aspect_ratio)
+        };
+        let (rect, _response) = ui.allocate_exact_size(size, egui::Sense::drag());
 
         ui.painter().add(egui_wgpu::Callback::new_paint_callback(
             rect,
             SceneSlice {
-                time: self.clock.elapsed().unwrap().as_secs_f32(),
-                lifecycles: self.lifecycles.clone(),
+                time: scene.time(),
+                timeline: scene.timeline.clone(),
             },
         ));
     }
 }
 
+pub struct MyApp {
+    project: Project,
+    chapter: Option<LoadedChapter>,
+}
+
+impl MyApp {
+    fn new(cc: &eframe::CreationContext<'_>, project_path: PathBuf) -> Self {
+        Self {
+            project: Project::watch(&project_path, cc.egui_ctx.clone()).unwrap_or_else(|error| {
+                panic!("Failed to watch {}: {error}", project_path.display())
+            }),
+            chapter: None,
+        }
+    }
+
+    // Swaps in the latest build of the project. On failure, the previous chapter stays.
+    fn reload(&mut self, frame: &eframe::Frame) {
+        let Some(reload) = self.project.try_reload() else {
+            return;
+        };
+        let previous = self
+            .chapter
+            .as_ref()
+            .and_then(|chapter| chapter.scene.as_ref())
+            .map(|scene| (scene.name.as_str(), scene.time()));
+        let chapter = reload
+            .map_err(|error| error.to_string())
+            .and_then(|reload| {
+                LoadedChapter::new(reload, previous).map_err(|error| error.to_string())
+            });
+        match chapter {
+            Ok(chapter) => {
+                // Prepared resources may hold vtables from the old library, so they go before it.
+                clear_timeline_resources(frame);
+                self.chapter = Some(chapter);
+            }
+            Err(error) => eprintln!(
+                "Failed to reload {}: {error}",
+                self.project.path().display()
+            ),
+        }
+    }
+}
+
+// Resources are keyed by entry index, so they must not outlive the timeline they were set up for.
+fn clear_timeline_resources(frame: &eframe::Frame) {
+    if let Some(render_state) = frame.wgpu_render_state() {
+        render_state
+            .renderer
+            .write()
+            .callback_resources
+            .remove::<TimelineResources>();
+    }
+}
+
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &eframe::egui::Context, frame: &mut eframe::Frame) {
+        self.reload(frame);
+        egui::TopBottomPanel::top("project").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if let Some(chapter) = &mut self.chapter
+                    && chapter.scene_picker(ui)
+                {
+                    clear_timeline_resources(frame);
+                }
+                if self.project.is_building() {
+                    ui.spinner();
+                }
+            });
+        });
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.custom_painting(ui);
+            if let Some(chapter) = &self.chapter {
+                chapter.custom_painting(ui);
+            }
         });
+        ctx.request_repaint();
     }
 }
 
+// Usage: `morphing-gui [chapter crate]`, defaulting to the current directory.
 fn main() -> eframe::Result {
+    morphing_core::runner_mode();
+    let project_path = std::env::args_os()
+        .nth(1)
+        .map_or_else(|| PathBuf::from("."), PathBuf::from);
     eframe::run_native(
         "Morphing App",
-        Default::default(),
-        Box::new(|cc| Ok(Box::new(MyApp::new(cc, "../examples/hello_morphing")))),
+        eframe::NativeOptions {
+            renderer: eframe::Renderer::Wgpu,
+            ..Default::default()
+        },
+        Box::new(|cc| Ok(Box::new(MyApp::new(cc, project_path)))),
     )
 }
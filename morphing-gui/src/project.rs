@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use morphing_core::ChapterBuildError;
+use morphing_core::ChapterLoadError;
+use morphing_core::ChapterSymbol;
+use morphing_core::Failure;
+use morphing_core::LineOutput;
+use morphing_core::SceneData;
+use morphing_core::build_chapter;
+use morphing_core::call_entrypoint;
+use morphing_core::run_isolated;
+
+// Editors often write a file in several steps; wait for them to settle before rebuilding.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+#[derive(Debug)]
+pub enum ReloadError {
+    Build(ChapterBuildError),
+    Copy(std::io::Error),
+    Run(Failure),
+    Load(ChapterLoadError),
+}
+
+impl std::fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Build(error) => write!(f, "{error}"),
+            Self::Copy(error) => write!(f, "Failed to copy chapter library: {error}"),
+            Self::Run(failure) => write!(f, "Failed to load chapter in runner: {failure}"),
+            Self::Load(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
+pub struct Reload {
+    pub chapter_symbol: ChapterSymbol,
+    // Scenes are first run in runner processes. Only those which succeeded there are run in the GUI.
+    pub scene_results: HashMap<String, Result<SceneData, Failure>>,
+    pub library_copy: LibraryCopy,
+}
+
+// The copy of the chapter library a generation is loaded from, removed when dropped.
+// Keep it until the `ChapterSymbol` loaded from it is gone, as some platforms
+// refuse to remove a loaded library.
+pub struct LibraryCopy(PathBuf);
+
+impl Drop for LibraryCopy {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.0).ok();
+    }
+}
+
+// A chapter crate, rebuilt and reloaded whenever anything under its `src/` changes.
+pub struct Project {
+    path: PathBuf,
+    building: Arc<AtomicBool>,
+    reloads: mpsc::Receiver<Result<Reload, ReloadError>>,
+    // Dropping the watcher ends the build thread.
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl Project {
+    // `ctx` is repainted whenever a reload is ready.
+    pub fn watch(path: impl Into<PathBuf>, ctx: egui::Context) -> notify::Result<Self> {
+        let path = path.into();
+        let building = Arc::new(AtomicBool::new(true));
+        let (change_sender, change_receiver) = mpsc::channel();
+        let (reload_sender, reloads) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if event.is_ok_and(|event| {
+                    matches!(
+                        event.kind,
+                        notify::EventKind::Create(_)
+                            | notify::EventKind::Modify(_)
+                            | notify::EventKind::Remove(_)
+                    )
+                }) {
+                    change_sender.send(()).ok();
+                }
+            })?;
+        notify::Watcher::watch(
+            &mut watcher,
+            &path.join("src"),
+            notify::RecursiveMode::Recursive,
+        )?;
+        std::thread::spawn({
+            let path = path.clone();
+            let building = building.clone();
+            move || {
+                for generation in 0.. {
+                    building.store(true, Ordering::Relaxed);
+                    let reload = reload(&path, generation);
+                    building.store(false, Ordering::Relaxed);
+                    if reload_sender.send(reload).is_err() {
+                        break;
+                    }
+                    ctx.request_repaint();
+                    if change_receiver.recv().is_err() {
+                        break;
+                    }
+                    while change_receiver.recv_timeout(DEBOUNCE).is_ok() {}
+                }
+            }
+        });
+        Ok(Self {
+            path,
+            building,
+            reloads,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_building(&self) -> bool {
+        self.building.load(Ordering::Relaxed)
+    }
+
+    // The latest finished reload, if any since the last call.
+    pub fn try_reload(&self) -> Option<Result<Reload, ReloadError>> {
+        self.reloads.try_iter().last()
+    }
+}
+
+fn reload(path: &Path, generation: usize) -> Result<Reload, ReloadError> {
+    let library_path = build_chapter(path).map_err(ReloadError::Build)?;
+    // Loaders cache libraries by path, so every generation is loaded from a fresh copy.
+    let copy_path = std::env::temp_dir().join("morphing").join(format!(
+        "{}-{}-{generation}.{}",
+        library_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy(),
+        std::process::id(),
+        std::env::consts::DLL_EXTENSION,
+    ));
+    std::fs::create_dir_all(copy_path.parent().unwrap()).map_err(ReloadError::Copy)?;
+    let library_copy = LibraryCopy(copy_path.clone());
+    std::fs::copy(&library_path, &copy_path).map_err(ReloadError::Copy)?;
+    let mut scene_results = HashMap::new();
+    for line_output in run_isolated(&copy_path) {
+        match line_output {
+            LineOutput::Project(output) => {
+                print_lines("chapter", &output.stdout_lines, &output.stderr_lines);
+                output.result.map_err(ReloadError::Run)?;
+            }
+            LineOutput::Scene(name, output) => {
+                print_lines(&name, &output.stdout_lines, &output.stderr_lines);
+                if let Err(failure) = &output.result {
+                    eprintln!("[{name}] {failure}");
+                }
+                scene_results.insert(name, output.result);
+            }
+        }
+    }
+    let chapter_symbol = call_entrypoint(&copy_path).map_err(ReloadError::Load)?;
+    if let Some(target_dir) = library_path.parent()
+        && let Err(error) = chapter_symbol.emit_schema(target_dir)
+    {
+        eprintln!("Failed to write config schema: {error}");
+    }
+    Ok(Reload {
+        chapter_symbol,
+        scene_results,
+        library_copy,
+    })
+}
+
+fn print_lines(name: &str, stdout_lines: &[String], stderr_lines: &[String]) {
+    for line in stdout_lines {
+        println!("[{name}] {line}");
+    }
+    for line in stderr_lines {
+        eprintln!("[{name}] {line}");
+    }
+}
@@ -1,8 +1,6 @@
 use super::root;
 use darling::{FromDeriveInput, FromField};
 
-pub struct Key<const KEY: u64>;
-
 #[derive(FromDeriveInput)]
 #[darling(supports(struct_named))]
 pub(crate) struct StructInfo {
@@ -19,11 +17,23 @@ struct FieldInfo {
     ty: syn::Type,
 }
 
+fn field_key(ident: &syn::Ident) -> u64 {
+    const_fnv1a_hash::fnv1a_hash_str_64(&ident.to_string())
+}
+
+// Every field is indexed by its own key, while a path `(key, rest)` indexes into the field
+// with the rest of the path, so that nested fields are reached through chained `Index` impls.
 pub(crate) fn field_index_derive(struct_info: StructInfo) -> proc_macro2::TokenStream {
     let name = struct_info.ident;
-    let generics_params = struct_info.generics.params;
-    let generics_where_clause = struct_info.generics.where_clause;
-    let fields_impl = struct_info
+    let (impl_generics, ty_generics, where_clause) = struct_info.generics.split_for_impl();
+    let mut path_generics = struct_info.generics.clone();
+    path_generics.params.insert(0, syn::parse_quote!(__P));
+    let (path_impl_generics, _, _) = path_generics.split_for_impl();
+    let where_predicates = where_clause
+        .map(|where_clause| where_clause.predicates.iter().collect())
+        .unwrap_or_else(Vec::new);
+
+    struct_info
         .data
         .take_struct()
         .unwrap()
@@ -32,65 +42,75 @@ pub(crate) fn field_index_derive(struct_info: StructInfo) -> proc_macro2::TokenS
         .map(|field_info| {
             let field_ident = field_info.ident.as_ref().unwrap();
             let field_ty = &field_info.ty;
-            let key = const_fnv1a_hash::fnv1a_hash_str_64(&field_ident.to_string());
+            let key = field_key(field_ident);
 
             quote::quote! {
-                impl<#generics_params> ::std::ops::Index<#root::__macros::field_index::Key<#key>> for #name #generics_where_clause {
+                impl #impl_generics ::std::ops::Index<#root::Key<#key>> for #name #ty_generics #where_clause {
                     type Output = #field_ty;
 
-                    fn index(&self) -> &Self::Output {
+                    fn index(&self, _: #root::Key<#key>) -> &Self::Output {
                         &self.#field_ident
                     }
                 }
 
-                impl<#generics_params> ::std::ops::IndexMut<#root::__macros::field_index::Key<#key>> for #name #generics_where_clause {
-                    type Output = #field_ty;
-
-                    fn index_mut(&mut self) -> &mut Self::Output {
+                impl #impl_generics ::std::ops::IndexMut<#root::Key<#key>> for #name #ty_generics #where_clause {
+                    fn index_mut(&mut self, _: #root::Key<#key>) -> &mut Self::Output {
                         &mut self.#field_ident
                     }
                 }
+
+                impl #path_impl_generics ::std::ops::Index<(#root::Key<#key>, __P)> for #name #ty_generics
+                where
+                    #field_ty: ::std::ops::Index<__P>,
+                    #(#where_predicates,)*
+                {
+                    type Output = <#field_ty as ::std::ops::Index<__P>>::Output;
+
+                    fn index(&self, (_, path): (#root::Key<#key>, __P)) -> &Self::Output {
+                        &self.#field_ident[path]
+                    }
+                }
+
+                impl #path_impl_generics ::std::ops::IndexMut<(#root::Key<#key>, __P)> for #name #ty_generics
+                where
+                    #field_ty: ::std::ops::IndexMut<__P>,
+                    #(#where_predicates,)*
+                {
+                    fn index_mut(&mut self, (_, path): (#root::Key<#key>, __P)) -> &mut Self::Output {
+                        &mut self.#field_ident[path]
+                    }
+                }
             }
         })
-        .collect();
+        .collect()
+}
 
-    quote::quote! {
-        impl<const __KEY: u64, __FP, #generics_params> Get<(#root::__macros::field_index::Key<__KEY>, __FP)> for #name
-        where
-            #name: ::std::ops::Index<#root::__macros::field_index::Key<KEY>>,
-            T::Output: ::std::ops::Index<__FP>,
-        {
-            type Output = <T::Output as Get<K>>::Output;
+pub(crate) struct FieldPath(syn::punctuated::Punctuated<syn::Ident, syn::Token![.]>);
 
-            fn get(&self, key: (__Key<KEY>, K)) -> &Self::Output {
-                self.get(key.0).get(key.1)
-            }
-        }
+impl syn::parse::Parse for FieldPath {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        syn::punctuated::Punctuated::parse_separated_nonempty(input).map(Self)
     }
 }
 
-pub(crate) fn field_path(
-    punctuated: syn::punctuated::Punctuated<syn::Ident, syn::Token![.]>,
-) -> proc_macro2::TokenStream {
-    let mut keys: Vec<_> = punctuated
+// `fp!(a.b.c)` expands to the value `(Key::<A>, (Key::<B>, Key::<C>))`,
+// where `A`, `B` and `C` are the hashes of the field names.
+pub(crate) fn field_path(field_path: FieldPath) -> proc_macro2::TokenStream {
+    let mut keys: Vec<_> = field_path
+        .0
         .iter()
         .map(|ident| {
-            let key = const_fnv1a_hash::fnv1a_hash_str_64(&ident.to_string());
+            let key = field_key(ident);
             quote::quote! {
-                #root::__macros::field_index::Key<#key>
+                #root::Key::<#key>
             }
         })
         .collect();
-    if keys.is_empty() {
-        proc_macro2::TokenStream::new()
-    } else {
-        while keys.len() > 1 {
-            let k = keys.pop().unwrap();
-            let key = keys.pop().unwrap();
-            keys.push(quote::quote! {
-                (#key, #k)
-            });
-        }
-        keys.pop().unwrap()
+    let mut path = keys.pop().unwrap();
+    while let Some(key) = keys.pop() {
+        path = quote::quote! {
+            (#key, #path)
+        };
     }
+    path
 }
@@ -0,0 +1,125 @@
+use super::root;
+use darling::{FromDeriveInput, FromField};
+
+#[derive(FromDeriveInput)]
+#[darling(supports(struct_any))]
+pub(crate) struct StructInfo {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<(), FieldInfo>,
+}
+
+// Fields are interpolated with their own `Interpolate` impl by default.
+// `#[interpolate(step)]` switches halfway instead, and
+// `#[interpolate(with = "path")]` calls `path(&self.field, &other.field, alpha)`.
+#[derive(FromField)]
+#[darling(attributes(interpolate))]
+struct FieldInfo {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    step: darling::util::Flag,
+    with: Option<syn::Path>,
+}
+
+pub(crate) fn interpolate_derive(struct_info: StructInfo) -> proc_macro2::TokenStream {
+    let name = struct_info.ident;
+    let fields = struct_info.data.take_struct().unwrap();
+    let field_errors = fields
+        .iter()
+        .filter(|field_info| field_info.step.is_present() && field_info.with.is_some())
+        .map(|field_info| {
+            darling::Error::custom("`step` and `with` cannot be specified together")
+                .with_span(&field_info.ty)
+        })
+        .collect::<Vec<_>>();
+    if !field_errors.is_empty() {
+        return darling::Error::multiple(field_errors).write_errors();
+    }
+
+    let mut generics = struct_info.generics;
+    let where_clause = generics.make_where_clause();
+    for field_info in fields.iter() {
+        if !field_info.step.is_present() && field_info.with.is_none() {
+            let field_ty = &field_info.ty;
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#field_ty: #root::Interpolate));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let field_members: Vec<syn::Member> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field_info)| {
+            field_info
+                .ident
+                .clone()
+                .map(syn::Member::Named)
+                .unwrap_or_else(|| syn::Member::Unnamed(index.into()))
+        })
+        .collect();
+    let field_values = fields
+        .iter()
+        .zip(&field_members)
+        .map(|(field_info, member)| {
+            if field_info.step.is_present() {
+                quote::quote! {
+                    #root::interpolate_step(&self.#member, &other.#member, alpha)
+                }
+            } else if let Some(with) = &field_info.with {
+                quote::quote! {
+                    #with(&self.#member, &other.#member, alpha)
+                }
+            } else {
+                quote::quote! {
+                    #root::Interpolate::interpolate(&self.#member, &other.#member, alpha)
+                }
+            }
+        });
+
+    quote::quote! {
+        impl #impl_generics #root::Interpolate for #name #ty_generics #where_clause {
+            fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+                Self {
+                    #(#field_members: #field_values,)*
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod interpolate_tests {
+    use super::*;
+
+    fn expand(derive_input: syn::DeriveInput) -> String {
+        interpolate_derive(StructInfo::from_derive_input(&derive_input).unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_field_bounds() {
+        let expanded = expand(syn::parse_quote! {
+            struct Labeled<T, L> {
+                value: T,
+                #[interpolate(step)]
+                label: L,
+            }
+        });
+        assert!(expanded.contains("T : :: morphing :: Interpolate"));
+        assert!(!expanded.contains("L : :: morphing :: Interpolate"));
+        assert!(expanded.contains(":: morphing :: interpolate_step (& self . label"));
+    }
+
+    #[test]
+    fn test_step_with_conflict() {
+        let expanded = expand(syn::parse_quote! {
+            struct Labeled {
+                #[interpolate(step, with = "interpolate_label")]
+                label: String,
+            }
+        });
+        assert!(expanded.contains("compile_error"));
+        assert!(expanded.contains("`step` and `with` cannot be specified together"));
+    }
+}
@@ -1,6 +1,7 @@
-// TODO: rate, world, layer, scene, wgpu_struct, wgpu_shader_types, pipeline?,
+// TODO: scene, pipeline?,
 
 mod field_index;
+mod interpolate;
 mod link;
 mod rate;
 mod structure;
@@ -23,9 +24,9 @@ fn delegate_macro<T>(f: fn(T) -> proc_macro2::TokenStream, tokens: TokenStream)
 where
     T: syn::parse::Parse,
 {
-    syn::parse::Parse::parse(tokens)
+    syn::parse(tokens)
         .map(f)
-        .unwrap_or_else(darling::Error::write_errors)
+        .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
 
@@ -56,7 +57,7 @@ fn delegate_macro_derive<T>(
 where
     T: darling::FromDeriveInput,
 {
-    T::from_derive_input(&syn::parse_macro_input!(tokens as T))
+    T::from_derive_input(&syn::parse_macro_input!(tokens as syn::DeriveInput))
         .map(f)
         .unwrap_or_else(darling::Error::write_errors)
         .into()
@@ -67,17 +68,52 @@ pub fn scene(input: TokenStream, tokens: TokenStream) -> TokenStream {
     delegate_macro_attribute(link::scene, input, tokens)
 }
 
+#[proc_macro_attribute]
+pub fn layer(input: TokenStream, tokens: TokenStream) -> TokenStream {
+    delegate_macro_attribute(structure::layer, input, tokens)
+}
+
+#[proc_macro_attribute]
+pub fn world(input: TokenStream, tokens: TokenStream) -> TokenStream {
+    delegate_macro_attribute(structure::world, input, tokens)
+}
+
 #[proc_macro_attribute]
 pub fn chapter(input: TokenStream, tokens: TokenStream) -> TokenStream {
     delegate_macro_attribute(link::chapter, input, tokens)
 }
 
+#[proc_macro_attribute]
+pub fn rate(input: TokenStream, tokens: TokenStream) -> TokenStream {
+    delegate_macro_attribute(rate::rate, input, tokens)
+}
+
 #[proc_macro]
 pub fn fp(tokens: TokenStream) -> TokenStream {
     delegate_macro(field_index::field_path, tokens)
 }
 
+#[proc_macro]
+pub fn wgpu_struct(tokens: TokenStream) -> TokenStream {
+    delegate_macro(wgpu::wgpu_struct, tokens)
+}
+
+#[proc_macro]
+pub fn wgpu_shader_types(tokens: TokenStream) -> TokenStream {
+    delegate_macro(wgpu::wgpu_shader_types, tokens)
+}
+
+#[proc_macro]
+pub fn include_wgsl(tokens: TokenStream) -> TokenStream {
+    delegate_macro(wgpu::include_wgsl, tokens)
+}
+
 #[proc_macro_derive(FieldIndex)]
 pub fn get_field_derive(tokens: TokenStream) -> TokenStream {
     delegate_macro_derive(field_index::field_index_derive, tokens)
 }
+
+#[proc_macro_derive(Interpolate, attributes(interpolate))]
+pub fn interpolate_derive(tokens: TokenStream) -> TokenStream {
+    delegate_macro_derive(interpolate::interpolate_derive, tokens)
+}
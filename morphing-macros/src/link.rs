@@ -1,75 +1,7 @@
-pub use config;
-pub use inventory;
-
 use super::root;
 use convert_case::Casing;
 use darling::FromMeta;
 
-pub struct Symbol<T> {
-    pub(crate) name: String,
-    pub(crate) config: Vec<config::File<config::FileSourceString, config::FileFormat>>,
-    pub(crate) content: T,
-}
-
-inventory::collect!(SceneSymbol);
-
-fn f(i: u32) -> () {
-    i
-}
-
-pub type SceneSymbol = Symbol<
-    Box<
-        dyn Fn(config::ConfigBuilder<config::builder::DefaultState>) -> Vec<Box<dyn Lifecycle>>
-            + Sync,
-    >,
->;
-
-pub fn scene_symbol<C: 'static + serde::de::DeserializeOwned, const N: usize>(
-    name: &str,
-    config: [config::File<config::FileSourceString, config::FileFormat>; N],
-    scene: fn(&mut Supervisor<C>),
-) -> SceneSymbol {
-    Symbol {
-        name: name.into(),
-        config: config.into(),
-        content: Box::new(move |config_builder| {
-            let configuration = config_builder.build().unwrap().try_deserialize().unwrap();
-            let mut supervisor = Supervisor {
-                time: 0.0,
-                lifecycles: Vec::new(),
-                config: configuration,
-            };
-            scene(&mut supervisor);
-            supervisor.lifecycles
-        }),
-    }
-}
-
-pub type ChapterSymbol = Symbol<std::collections::HashMap<String, &'static SceneSymbol>>;
-
-pub fn chapter_symbol<const N: usize>(
-    name: &str,
-    config: [config::File<config::FileSourceString, config::FileFormat>; N],
-    scenes: inventory::iter<SceneSymbol>,
-) -> ChapterSymbol {
-    Symbol {
-        name: name.into(),
-        config: config.into(),
-        content: scenes
-            .into_iter()
-            .map(|symbol| (symbol.name.clone(), symbol))
-            .collect(),
-    }
-}
-
-pub(crate) fn call_entrypoint(chapter_path: &str) -> ChapterSymbol {
-    let func: libloading::Symbol<extern "Rust" fn() -> ChapterSymbol> = unsafe {
-        let lib = libloading::Library::new(chapter_path).unwrap();
-        lib.get(b"__morphing_entrypoint__\0").unwrap(); // expecting #[chapter] invocation
-    };
-    func()
-}
-
 // pub mod config_formats {
 //     macro_rules! config_format {
 //         ($name:ident = $format:expr) => {
@@ -105,8 +37,17 @@ impl From<Vec<syn::MetaNameValue>> for NameValueList {
 }
 
 impl FromMeta for NameValueList {
+    // `config = "scene.toml"` is a shorthand for `config(file = "scene.toml")`.
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        if let syn::Lit::Str(path) = value {
+            Ok(NameValueList(vec![syn::parse_quote!(file = #path)]))
+        } else {
+            Err(darling::Error::unexpected_lit_type(value))
+        }
+    }
+
     fn from_list(v: &[darling::ast::NestedMeta]) -> darling::Result<Self> {
-        v.into_iter()
+        v.iter()
             .map(|nm| {
                 if let darling::ast::NestedMeta::Meta(syn::Meta::NameValue(ref name_value)) = *nm {
                     Ok(name_value.clone())
@@ -119,76 +60,235 @@ impl FromMeta for NameValueList {
     }
 }
 
+// Either a file of variant tables, `variants = "variants.toml"`,
+// or inline configs per variant, `variants(en(toml = "..."), zh(toml = "..."))`.
+enum VariantsArg {
+    File(syn::LitStr),
+    List(Vec<(syn::Ident, NameValueList)>),
+}
+
+impl FromMeta for VariantsArg {
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        if let syn::Lit::Str(path) = value {
+            Ok(VariantsArg::File(path.clone()))
+        } else {
+            Err(darling::Error::unexpected_lit_type(value))
+        }
+    }
+
+    fn from_list(v: &[darling::ast::NestedMeta]) -> darling::Result<Self> {
+        v.iter()
+            .map(|nm| {
+                if let darling::ast::NestedMeta::Meta(syn::Meta::List(ref list)) = *nm {
+                    let name = list.path.require_ident()?.clone();
+                    let items = darling::ast::NestedMeta::parse_meta_list(list.tokens.clone())?;
+                    Ok((name, NameValueList::from_list(&items)?))
+                } else {
+                    Err(darling::Error::unexpected_type("non-list").with_span(nm))
+                }
+            })
+            .collect::<darling::Result<_>>()
+            .map(VariantsArg::List)
+    }
+}
+
 #[derive(FromMeta)]
-pub(crate) struct ConfigArgs {
+pub(crate) struct ChapterArgs {
     #[darling(default)]
     config: NameValueList,
+    // Lets loaders write `<chapter>.schema.json` into the target dir, see `ChapterSymbol::emit_schema`.
+    #[darling(default)]
+    schema: bool,
+}
+
+#[derive(FromMeta)]
+pub(crate) struct SceneArgs {
+    #[darling(default)]
+    config: NameValueList,
+    variants: Option<VariantsArg>,
 }
 
-fn expand_configs(config: NameValueList) -> proc_macro2::TokenStream {
-    let items: Vec::<_> = config
+// Each item is either `file = "path"`, resolved against `CARGO_MANIFEST_DIR` with the format
+// inferred from the extension, or `format = "content"` for inline configs.
+fn expand_configs(config: NameValueList) -> syn::Result<proc_macro2::TokenStream> {
+    let items = config
         .iter()
         .map(|name_value| {
-            let format = name_value
-                .path
-                .require_ident()
-                .expect("Format specifier must be an identifier");
+            let format = name_value.path.require_ident()?;
+            if format == "file" {
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(path),
+                    ..
+                }) = &name_value.value
+                else {
+                    return Err(syn::Error::new_spanned(
+                        &name_value.value,
+                        "Config file path must be a string literal",
+                    ));
+                };
+                return include_config_file(path);
+            }
             let format = syn::Ident::new(
                 &format.to_string().to_case(convert_case::Case::Pascal),
                 format.span(),
             );
             let literal = &name_value.value;
 
-            quote::quote! {
-            	#root::__macros::link::config::File::from_str(#literal, #root::__macros::link::config::FileFormat::#format)
-            }
+            Ok(quote::quote! {
+            	#root::__link::config::File::from_str(#literal, #root::__link::config::FileFormat::#format)
+            })
         })
-        .collect();
+        .collect::<syn::Result<Vec<_>>>()?;
 
-    quote::quote! {
+    Ok(quote::quote! {
         [#(#items),*]
+    })
+}
+
+// Goes through `include_str!` so that edits to the file trigger rebuilds.
+fn include_config_file(path: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let format = file_format(path)?;
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(path.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let full_path = std::path::Path::new(&manifest_dir).join(path.value());
+    if !full_path.is_file() {
+        return Err(syn::Error::new(
+            path.span(),
+            format!("Config file not found: {}", full_path.display()),
+        ));
     }
+    let full_path = full_path.to_string_lossy();
+
+    Ok(quote::quote! {
+        #root::__link::config::File::from_str(
+            include_str!(#full_path),
+            #root::__link::config::FileFormat::#format,
+        )
+    })
+}
+
+fn file_format(path: &syn::LitStr) -> syn::Result<syn::Ident> {
+    let format = match std::path::Path::new(&path.value())
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+    {
+        Some("toml") => "Toml",
+        Some("json") => "Json",
+        Some("yaml" | "yml") => "Yaml",
+        Some("ini") => "Ini",
+        Some("ron") => "Ron",
+        Some("json5") => "Json5",
+        _ => {
+            return Err(syn::Error::new(
+                path.span(),
+                "Cannot infer config format from file extension",
+            ));
+        }
+    };
+    Ok(syn::Ident::new(format, path.span()))
+}
+
+fn expand_variants(variants: Option<VariantsArg>) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(match variants {
+        None => quote::quote! {
+            ::std::result::Result::Ok(::std::vec::Vec::new())
+        },
+        Some(VariantsArg::File(path)) => {
+            let file = include_config_file(&path)?;
+            quote::quote! {
+                #root::__link::variants_from_file(#file)
+            }
+        }
+        Some(VariantsArg::List(list)) => {
+            let items = list
+                .into_iter()
+                .map(|(name, config)| {
+                    let name = name.to_string();
+                    let config_expanded = expand_configs(config)?;
+                    Ok(quote::quote! {
+                        (#name, ::std::vec::Vec::from(#config_expanded))
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote::quote! {
+                #root::__link::variants_from_list([#(#items),*])
+            }
+        }
+    })
 }
 
-pub(crate) fn scene(args: ConfigArgs, item_fn: syn::ItemFn) -> proc_macro2::TokenStream {
+pub(crate) fn scene(args: SceneArgs, item_fn: syn::ItemFn) -> proc_macro2::TokenStream {
     let ident = &item_fn.sig.ident;
     let name = ident.to_string();
-    let config_expanded = expand_configs(args.config);
+    let (config_expanded, variants_expanded) =
+        match expand_configs(args.config).and_then(|config_expanded| {
+            Ok((config_expanded, expand_variants(args.variants)?))
+        }) {
+            Ok(expanded) => expanded,
+            Err(error) => return error.into_compile_error(),
+        };
 
     quote::quote! {
         #item_fn
 
-        #root::__macros::link::inventory::submit! {
-            #root::__macros::link::scene_symbol(
-                concat!(module_path!(), "::", #name),
-                #config_expanded,
-                #ident,
-            )
+        #root::__link::inventory::submit! {
+            #root::SceneSymbol {
+                name: concat!(module_path!(), "::", #name),
+                config: || ::std::vec::Vec::from(#config_expanded),
+                variants: || #variants_expanded,
+                scene: |layers| #root::__link::run_scene(layers, #ident),
+                schema: |generator| {
+                    #[allow(unused_imports)]
+                    use #root::__link::SceneSchema as _;
+                    #[allow(unused_imports)]
+                    use #root::__link::SceneSchemaFallback as _;
+                    (&#root::__link::SchemaProbe::of(#ident)).scene_schema(generator)
+                },
+            }
         }
     }
 }
 
 pub(crate) fn chapter(
-    args: ConfigArgs,
+    args: ChapterArgs,
     item_extern_crate: syn::ItemExternCrate,
 ) -> proc_macro2::TokenStream {
-    assert_eq!(item_extern_crate.ident.to_string(), "self");
+    if item_extern_crate.ident != "self" {
+        return syn::Error::new_spanned(
+            &item_extern_crate.ident,
+            "expected `extern crate self;` or `extern crate self as name;`",
+        )
+        .into_compile_error();
+    }
     let root = root;
-    let name = item_extern_crate
-        .rename
-        .as_ref()
-        .map(|(_, rename)| rename.to_string())
-        .unwrap_or_else(|| std::env::var("CARGO_PKG_NAME").unwrap());
-    let config_expanded = expand_configs(args.config);
+    let name = match item_extern_crate.rename.as_ref() {
+        Some((_, rename)) => rename.to_string(),
+        None => match std::env::var("CARGO_PKG_NAME") {
+            Ok(name) => name,
+            Err(_) => {
+                return syn::Error::new_spanned(
+                    &item_extern_crate,
+                    "CARGO_PKG_NAME is not set, name the chapter with `extern crate self as name;`",
+                )
+                .into_compile_error();
+            }
+        },
+    };
+    let config_expanded = match expand_configs(args.config) {
+        Ok(config_expanded) => config_expanded,
+        Err(error) => return error.into_compile_error(),
+    };
+    let schema = args.schema;
 
+    // The symbol names are checked by `morphing_core::call_entrypoint`.
     quote::quote! {
-        #[no_mangle]
-        pub extern "Rust" fn __morphing_entrypoint__() -> #root::__macros::link::ChapterSymbol {
-            #root::__macros::link::chapter_symbol(
-                #name,
-                #config_expanded,
-                #root::__macros::link::inventory::iter,
-            )
+        #[allow(non_upper_case_globals)]
+        #[unsafe(no_mangle)]
+        pub static __morphing_abi__: #root::AbiDescriptor = #root::AbiDescriptor::CURRENT;
+
+        #[unsafe(no_mangle)]
+        pub extern "Rust" fn __morphing_entrypoint_v1__() -> #root::ChapterSymbol {
+            #root::__link::chapter_symbol(#name, ::std::vec::Vec::from(#config_expanded), #schema)
         }
     }
 }
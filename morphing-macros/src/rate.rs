@@ -1,16 +1,20 @@
 use darling::FromMeta;
 
+use super::root;
+
 #[derive(FromMeta)]
 pub(crate) struct RateArgs {
     normalized: darling::util::Flag,
     denormalized: darling::util::Flag,
     increasing: darling::util::Flag,
     assert: Option<syn::LitStr>,
+    // Closed-form inverse of an increasing rate, as an expression of the time argument
+    // (bound to the output) and the other arguments. Falls back to bisection if absent.
+    inverse: Option<syn::LitStr>,
 }
 
 // External crate dependencies:
 // - serde
-// - morphing_core
 pub(crate) fn rate(args: RateArgs, item_fn: syn::ItemFn) -> proc_macro2::TokenStream {
     let fn_name = &item_fn.sig.ident;
     let vis = &item_fn.vis;
@@ -35,54 +39,118 @@ pub(crate) fn rate(args: RateArgs, item_fn: syn::ItemFn) -> proc_macro2::TokenSt
         })
         .unzip();
 
+    if let Some(inverse) = args.inverse.as_ref().filter(|_| !args.increasing.is_present()) {
+        return syn::Error::new(inverse.span(), "`inverse` requires `increasing`")
+            .into_compile_error();
+    }
+    let inverse_expr = match args.inverse.map(|inverse_expression_str| {
+        let expr = inverse_expression_str.parse::<syn::Expr>()?;
+        let time_pat = match item_fn.sig.inputs.first() {
+            Some(syn::FnArg::Typed(syn::PatType { pat, .. })) => pat,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &item_fn.sig,
+                    "Rate function should take the time as its first argument",
+                ));
+            }
+        };
+        Ok(quote::quote! {
+            let #time_pat = *time_metric;
+            #(let #struct_field_names = self.#struct_field_names.clone();)*
+            (#expr).into()
+        })
+    }).transpose() {
+        Ok(inverse_expr) => inverse_expr,
+        Err(error) => return error.to_compile_error(),
+    };
+    let inverse_fn = |time_metric: proc_macro2::TokenStream| {
+        inverse_expr
+            .as_ref()
+            .map(|inverse_expr| {
+                quote::quote! {
+                    #[allow(unused_variables)]
+                    fn inverse(&self, time_metric: #time_metric) -> #time_metric {
+                        #inverse_expr
+                    }
+                }
+            })
+            .unwrap_or_default()
+    };
+    let normalized_inverse_fn = inverse_fn(quote::quote! { #root::NormalizedTimeMetric });
+    let denormalized_inverse_fn = inverse_fn(quote::quote! { #root::DenormalizedTimeMetric });
+
     let struct_definition = quote::quote! {
         #[allow(non_camel_case_types)]
         #[derive(Clone, Debug, ::serde::Deserialize, ::serde::Serialize)]
-        struct #struct_name {
+        #vis struct #struct_name {
             #(#struct_field_names: #struct_field_types,)*
         }
     };
 
-    let impl_normalized_rate = args.normalized.is_present().then(|| quote::quote! {
-        impl ::morphing_core::traits::Rate<::morphing_core::timer::NormalizedTimeMetric> for #struct_name {
-            type OutputTimeMetric = ::morphing_core::timer::NormalizedTimeMetric;
+    let impl_normalized_rate = if args.normalized.is_present() {
+        quote::quote! {
+            impl #root::Rate<#root::NormalizedTimeMetric> for #struct_name {
+                type OutputTimeMetric = #root::NormalizedTimeMetric;
 
-            fn eval(&self, time_metric: ::morphing_core::timer::NormalizedTimeMetric) -> Self::OutputTimeMetric {
-                #fn_name(*time_metric, #(self.#struct_field_names.clone()),*)
+                fn eval(&self, time_metric: #root::NormalizedTimeMetric) -> Self::OutputTimeMetric {
+                    #fn_name(*time_metric, #(self.#struct_field_names.clone()),*).into()
+                }
             }
         }
-    }).unwrap_or_default();
-    let impl_normalized_increasing_rate = (args.normalized.is_present() && args.increasing.is_present()).then(|| quote::quote! {
-        impl ::morphing_core::traits::IncreasingRate<::morphing_core::timer::NormalizedTimeMetric> for #struct_name {}
-    }).unwrap_or_default();
-    let impl_denormalized_rate = args.denormalized.is_present().then(|| quote::quote! {
-        impl ::morphing_core::traits::Rate<::morphing_core::timer::DenormalizedTimeMetric> for #struct_name {
-            type OutputTimeMetric = ::morphing_core::timer::DenormalizedTimeMetric;
+    } else {
+        proc_macro2::TokenStream::new()
+    };
+    let impl_normalized_increasing_rate =
+        if args.normalized.is_present() && args.increasing.is_present() {
+            quote::quote! {
+                impl #root::IncreasingRate<#root::NormalizedTimeMetric> for #struct_name {
+                    #normalized_inverse_fn
+                }
+            }
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+    let impl_denormalized_rate = if args.denormalized.is_present() {
+        quote::quote! {
+            impl #root::Rate<#root::DenormalizedTimeMetric> for #struct_name {
+                type OutputTimeMetric = #root::DenormalizedTimeMetric;
 
-            fn eval(&self, time_metric: ::morphing_core::timer::DenormalizedTimeMetric) -> Self::OutputTimeMetric {
-                #fn_name(*time_metric, #(self.#struct_field_names.clone()),*)
+                fn eval(&self, time_metric: #root::DenormalizedTimeMetric) -> Self::OutputTimeMetric {
+                    #fn_name(*time_metric, #(self.#struct_field_names.clone()),*).into()
+                }
             }
         }
-    }).unwrap_or_default();
-    let impl_denormalized_increasing_rate = (args.denormalized.is_present() && args.increasing.is_present()).then(|| quote::quote! {
-        impl ::morphing_core::traits::IncreasingRate<::morphing_core::timer::DenormalizedTimeMetric> for #struct_name {}
-    }).unwrap_or_default();
-
-    let assert_statement = args
-        .assert
-        .map(|assert_expression_str| {
-            let expr = assert_expression_str.parse::<syn::Expr>().unwrap();
+    } else {
+        proc_macro2::TokenStream::new()
+    };
+    let impl_denormalized_increasing_rate =
+        if args.denormalized.is_present() && args.increasing.is_present() {
             quote::quote! {
-                assert!(#expr);
+                impl #root::IncreasingRate<#root::DenormalizedTimeMetric> for #struct_name {
+                    #denormalized_inverse_fn
+                }
             }
-        })
-        .unwrap_or_default();
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+
+    let assert_statement = match args
+        .assert
+        .map(|assert_expression_str| assert_expression_str.parse::<syn::Expr>())
+        .transpose()
+    {
+        Ok(Some(expr)) => quote::quote! {
+            assert!(#expr);
+        },
+        Ok(None) => proc_macro2::TokenStream::new(),
+        Err(error) => return error.to_compile_error(),
+    };
     let trait_definition = quote::quote! {
         #[allow(non_camel_case_types)]
-        #vis trait #trait_name<TM>: ::morphing_core::timeline::ApplyRate<TM>
+        #vis trait #trait_name<TM>: #root::ApplyRate<TM>
         where
-            TM: ::morphing_core::timer::TimeMetric,
-            #struct_name: ::morphing_core::traits::Rate<TM>,
+            TM: #root::TimeMetric,
+            #struct_name: #root::Rate<TM>,
         {
             fn #fn_name(self, #(#struct_field_names: #struct_field_types),*) -> Self::Output<#struct_name> {
                 #assert_statement
@@ -93,9 +161,9 @@ pub(crate) fn rate(args: RateArgs, item_fn: syn::ItemFn) -> proc_macro2::TokenSt
     let blanket_impl = quote::quote! {
         impl<A, TM> #trait_name<TM> for A
         where
-            A: ::morphing_core::timeline::ApplyRate<TM>,
-            TM: ::morphing_core::timer::TimeMetric,
-            #struct_name: ::morphing_core::traits::Rate<TM>,
+            A: #root::ApplyRate<TM>,
+            TM: #root::TimeMetric,
+            #struct_name: #root::Rate<TM>,
         {}
     };
 
@@ -1 +1,756 @@
+use super::root;
 
+// WGSL types are written with Rust type syntax, e.g. `vec4<f32>`, `mat2x4<f32>`
+// or `array<GradientStopStorage>`. Anything not recognized as a WGSL builtin
+// is taken as a struct declared through `wgpu_struct!`.
+enum WgslType<'a> {
+    Builtin(proc_macro2::TokenStream),
+    Array(Box<WgslType<'a>>),
+    Struct(&'a syn::Type),
+}
+
+impl<'a> WgslType<'a> {
+    fn new(ty: &'a syn::Type) -> Self {
+        let syn::Type::Path(syn::TypePath { qself: None, path }) = ty else {
+            return Self::Struct(ty);
+        };
+        if path.leading_colon.is_some() || path.segments.len() != 1 {
+            return Self::Struct(ty);
+        }
+        let segment = &path.segments[0];
+        let name = segment.ident.to_string();
+        let type_param = match &segment.arguments {
+            syn::PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+                match &arguments.args[0] {
+                    syn::GenericArgument::Type(type_param) => Some(type_param),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match (name.as_str(), type_param) {
+            ("bool" | "i32" | "u32" | "f32" | "f16", None) => Self::Builtin(quote::quote! { #ty }),
+            ("array", Some(type_param)) => Self::Array(Box::new(Self::new(type_param))),
+            (name, Some(type_param)) => {
+                if let Some(rows) = name
+                    .strip_prefix("vec")
+                    .and_then(|rows| rows.parse::<usize>().ok())
+                    .filter(|rows| (2..=4).contains(rows))
+                {
+                    let vector = quote::format_ident!("Vector{rows}");
+                    Self::Builtin(quote::quote! { ::nalgebra::#vector<#type_param> })
+                } else if let Some((columns, rows)) = name
+                    .strip_prefix("mat")
+                    .and_then(|shape| shape.split_once('x'))
+                    .and_then(|(columns, rows)| {
+                        Some((columns.parse::<usize>().ok()?, rows.parse::<usize>().ok()?))
+                    })
+                    .filter(|(columns, rows)| (2..=4).contains(columns) && (2..=4).contains(rows))
+                {
+                    // WGSL `matCxR` has `C` columns and `R` rows, nalgebra `MatrixRxC` the other way round.
+                    let matrix = if columns == rows {
+                        quote::format_ident!("Matrix{rows}")
+                    } else {
+                        quote::format_ident!("Matrix{rows}x{columns}")
+                    };
+                    Self::Builtin(quote::quote! { ::nalgebra::#matrix<#type_param> })
+                } else {
+                    Self::Struct(ty)
+                }
+            }
+            _ => Self::Struct(ty),
+        }
+    }
+
+    fn rust_type(&self) -> proc_macro2::TokenStream {
+        match self {
+            Self::Builtin(rust_type) => rust_type.clone(),
+            Self::Array(element) => {
+                let element = element.rust_type();
+                quote::quote! { ::std::vec::Vec<#element> }
+            }
+            Self::Struct(ty) => quote::quote! { #ty },
+        }
+    }
+
+    // The struct whose members are laid out in a binding of this type.
+    fn member_struct_type(&self) -> Option<&'a syn::Type> {
+        match self {
+            Self::Builtin(_) => None,
+            Self::Array(element) => element.member_struct_type(),
+            Self::Struct(ty) => Some(ty),
+        }
+    }
+
+    fn struct_types(&self) -> Vec<&'a syn::Type> {
+        match self {
+            Self::Builtin(_) => Vec::new(),
+            Self::Array(element) => element.struct_types(),
+            Self::Struct(ty) => vec![ty],
+        }
+    }
+}
+
+fn wgsl_type_string(ty: &syn::Type) -> String {
+    quote::quote! { #ty }.to_string().replace(' ', "")
+}
+
+pub(crate) struct WgpuStructs(Vec<syn::ItemStruct>);
+
+impl syn::parse::Parse for WgpuStructs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut item_structs = Vec::new();
+        while !input.is_empty() {
+            item_structs.push(input.parse()?);
+        }
+        Ok(Self(item_structs))
+    }
+}
+
+// External crate dependencies:
+// - encase
+// - nalgebra
+// - morphing_core
+pub(crate) fn wgpu_struct(wgpu_structs: WgpuStructs) -> proc_macro2::TokenStream {
+    wgpu_structs
+        .0
+        .iter()
+        .map(|item_struct| {
+            let syn::Fields::Named(fields_named) = &item_struct.fields else {
+                return syn::Error::new_spanned(
+                    &item_struct.fields,
+                    "expected a struct with named fields",
+                )
+                .into_compile_error();
+            };
+            let attrs = &item_struct.attrs;
+            let vis = &item_struct.vis;
+            let name = &item_struct.ident;
+            let field_vises = fields_named.named.iter().map(|field| &field.vis);
+            let field_idents: Vec<_> = fields_named
+                .named
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap())
+                .collect();
+            let field_tys = fields_named
+                .named
+                .iter()
+                .map(|field| WgslType::new(&field.ty).rust_type());
+            let wgsl_declaration = format!(
+                "struct {name} {{\n{}}}\n",
+                fields_named
+                    .named
+                    .iter()
+                    .map(|field| format!(
+                        "    {}: {},\n",
+                        field.ident.as_ref().unwrap(),
+                        wgsl_type_string(&field.ty),
+                    ))
+                    .collect::<String>(),
+            );
+            let members = fields_named.named.iter().enumerate().map(|(index, field)| {
+                let member_name = field.ident.as_ref().unwrap().to_string();
+                let member_ty = wgsl_type_string(&field.ty);
+                let members = match WgslType::new(&field.ty).member_struct_type() {
+                    Some(ty) => quote::quote! { <#ty as #root::WgslStruct>::MEMBERS },
+                    None => quote::quote! { &[] },
+                };
+                quote::quote! {
+                    #root::WgslMember {
+                        name: #member_name,
+                        ty: #member_ty,
+                        offset: <Self as ::encase::ShaderType>::METADATA.offset(#index),
+                        members: #members,
+                    }
+                }
+            });
+            quote::quote! {
+                #(#attrs)*
+                #[derive(::encase::ShaderType)]
+                #vis struct #name {
+                    #(#field_vises #field_idents: #field_tys,)*
+                }
+
+                impl #root::WgslStruct for #name {
+                    const WGSL_DECLARATION: &'static str = #wgsl_declaration;
+                    const MEMBERS: &'static [#root::WgslMember] = &[#(#members,)*];
+                }
+            }
+        })
+        .collect()
+}
+
+enum AddressSpace {
+    Uniform,
+    Storage,
+}
+
+struct WgpuBinding {
+    stages: Vec<syn::Ident>,
+    index: syn::LitInt,
+    address_space: AddressSpace,
+    ident: syn::Ident,
+    ty: syn::Type,
+}
+
+impl syn::parse::Parse for WgpuBinding {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        // pub(FRAGMENT) @binding(0) var<uniform> u_color: ColorUniform
+        input.parse::<syn::Token![pub]>()?;
+        let stages_content;
+        syn::parenthesized!(stages_content in input);
+        let stages =
+            syn::punctuated::Punctuated::<syn::Ident, syn::Token![|]>::parse_separated_nonempty(
+                &stages_content,
+            )?
+            .into_iter()
+            .collect();
+        input.parse::<syn::Token![@]>()?;
+        let binding = input.parse::<syn::Ident>()?;
+        if binding != "binding" {
+            return Err(syn::Error::new(binding.span(), "expected `binding`"));
+        }
+        let index_content;
+        syn::parenthesized!(index_content in input);
+        let index = index_content.parse()?;
+        let var = input.parse::<syn::Ident>()?;
+        if var != "var" {
+            return Err(syn::Error::new(var.span(), "expected `var`"));
+        }
+        input.parse::<syn::Token![<]>()?;
+        let address_space_ident = input.parse::<syn::Ident>()?;
+        let address_space = match address_space_ident.to_string().as_str() {
+            "uniform" => AddressSpace::Uniform,
+            "storage" => AddressSpace::Storage,
+            _ => {
+                return Err(syn::Error::new(
+                    address_space_ident.span(),
+                    "expected `uniform` or `storage`",
+                ));
+            }
+        };
+        input.parse::<syn::Token![>]>()?;
+        let ident = input.parse()?;
+        input.parse::<syn::Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(Self {
+            stages,
+            index,
+            address_space,
+            ident,
+            ty,
+        })
+    }
+}
+
+pub(crate) struct WgpuShaderTypes {
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    bindings: Vec<WgpuBinding>,
+}
+
+impl syn::parse::Parse for WgpuShaderTypes {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        input.parse::<syn::Token![struct]>()?;
+        let ident = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let bindings =
+            syn::punctuated::Punctuated::<WgpuBinding, syn::Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .collect();
+        Ok(Self {
+            vis,
+            ident,
+            bindings,
+        })
+    }
+}
+
+// External crate dependencies:
+// - encase
+// - nalgebra
+// - wgpu
+// - morphing_core
+pub(crate) fn wgpu_shader_types(shader_types: WgpuShaderTypes) -> proc_macro2::TokenStream {
+    let WgpuShaderTypes {
+        vis,
+        ident: name,
+        bindings,
+    } = &shader_types;
+    let buffers_name = quote::format_ident!("{}Buffers", name);
+    let binding_idents: Vec<_> = bindings.iter().map(|binding| &binding.ident).collect();
+    let binding_indices: Vec<_> = bindings.iter().map(|binding| &binding.index).collect();
+    let wgsl_types: Vec<_> = bindings
+        .iter()
+        .map(|binding| WgslType::new(&binding.ty))
+        .collect();
+    let rust_types: Vec<_> = wgsl_types
+        .iter()
+        .map(|wgsl_type| wgsl_type.rust_type())
+        .collect();
+    let visibilities = bindings.iter().map(|binding| {
+        let stages = &binding.stages;
+        quote::quote! { #(::wgpu::ShaderStages::#stages)|* }
+    });
+    let buffer_binding_types = bindings.iter().map(|binding| match binding.address_space {
+        AddressSpace::Uniform => quote::quote! { ::wgpu::BufferBindingType::Uniform },
+        AddressSpace::Storage => {
+            quote::quote! { ::wgpu::BufferBindingType::Storage { read_only: true } }
+        }
+    });
+    let buffer_usages: Vec<_> = bindings
+        .iter()
+        .map(|binding| match binding.address_space {
+            AddressSpace::Uniform => quote::quote! { ::wgpu::BufferUsages::UNIFORM },
+            AddressSpace::Storage => quote::quote! { ::wgpu::BufferUsages::STORAGE },
+        })
+        .collect();
+    let encase_buffers = bindings.iter().map(|binding| match binding.address_space {
+        AddressSpace::Uniform => quote::quote! { ::encase::UniformBuffer },
+        AddressSpace::Storage => quote::quote! { ::encase::StorageBuffer },
+    });
+
+    let mut struct_types: Vec<&syn::Type> = Vec::new();
+    for ty in wgsl_types.iter().flat_map(WgslType::struct_types) {
+        if !struct_types.contains(&ty) {
+            struct_types.push(ty);
+        }
+    }
+    let struct_declarations = struct_types
+        .iter()
+        .map(|ty| quote::quote! { <#ty as #root::WgslStruct>::WGSL_DECLARATION });
+    let binding_layouts = bindings.iter().zip(&wgsl_types).map(|(binding, wgsl_type)| {
+        let index = &binding.index;
+        let address_space = match binding.address_space {
+            AddressSpace::Uniform => "uniform",
+            AddressSpace::Storage => "storage",
+        };
+        let binding_name = binding.ident.to_string();
+        let binding_ty = wgsl_type_string(&binding.ty);
+        let rust_type = wgsl_type.rust_type();
+        let members = match wgsl_type.member_struct_type() {
+            Some(ty) => quote::quote! { <#ty as #root::WgslStruct>::MEMBERS },
+            None => quote::quote! { &[] },
+        };
+        quote::quote! {
+            #root::WgslBinding {
+                binding: #index,
+                address_space: #address_space,
+                name: #binding_name,
+                ty: #binding_ty,
+                size: <#rust_type as ::encase::ShaderType>::METADATA.min_size().get(),
+                members: #members,
+            }
+        }
+    });
+    let binding_declarations = bindings
+        .iter()
+        .map(|binding| {
+            format!(
+                "@group({{group}}) @binding({}) var<{}> {}: {};\n",
+                binding.index.base10_digits(),
+                match binding.address_space {
+                    AddressSpace::Uniform => "uniform",
+                    AddressSpace::Storage => "storage",
+                },
+                binding.ident,
+                wgsl_type_string(&binding.ty),
+            )
+        })
+        .collect::<String>();
+
+    quote::quote! {
+        #vis struct #name {
+            #(#binding_idents: #rust_types,)*
+        }
+
+        #vis struct #buffers_name {
+            #(#binding_idents: ::wgpu::Buffer,)*
+        }
+
+        impl #root::ComponentShaderTypes for #name {
+            type Buffers = #buffers_name;
+
+            const BINDINGS: &'static [#root::WgslBinding] = &[#(#binding_layouts,)*];
+
+            fn wgsl_declarations(group: u32) -> ::std::string::String {
+                let mut wgsl_declarations = ::std::string::String::new();
+                #(wgsl_declarations.push_str(#struct_declarations);)*
+                wgsl_declarations.push_str(&::std::format!(#binding_declarations, group = group));
+                wgsl_declarations
+            }
+
+            fn bind_group_layout(device: &::wgpu::Device) -> &'static ::wgpu::BindGroupLayout {
+                static BIND_GROUP_LAYOUT: ::std::sync::OnceLock<::wgpu::BindGroupLayout> =
+                    ::std::sync::OnceLock::new();
+                BIND_GROUP_LAYOUT.get_or_init(|| {
+                    device.create_bind_group_layout(&::wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[
+                            #(::wgpu::BindGroupLayoutEntry {
+                                binding: #binding_indices,
+                                visibility: #visibilities,
+                                ty: ::wgpu::BindingType::Buffer {
+                                    ty: #buffer_binding_types,
+                                    has_dynamic_offset: false,
+                                    min_binding_size: Some(
+                                        <#rust_types as ::encase::ShaderType>::min_size(),
+                                    ),
+                                },
+                                count: None,
+                            },)*
+                        ],
+                    })
+                })
+            }
+
+            fn bind_group_from_buffers(
+                device: &::wgpu::Device,
+                buffers: &Self::Buffers,
+            ) -> ::wgpu::BindGroup {
+                device.create_bind_group(&::wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: <Self as #root::ComponentShaderTypes>::bind_group_layout(device),
+                    entries: &[
+                        #(::wgpu::BindGroupEntry {
+                            binding: #binding_indices,
+                            resource: buffers.#binding_idents.as_entire_binding(),
+                        },)*
+                    ],
+                })
+            }
+
+            fn new_buffers(&self, device: &::wgpu::Device) -> Self::Buffers {
+                #buffers_name {
+                    #(#binding_idents: device.create_buffer(&::wgpu::BufferDescriptor {
+                        label: None,
+                        size: ::encase::ShaderType::size(&self.#binding_idents).get(),
+                        usage: #buffer_usages | ::wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),)*
+                }
+            }
+
+            fn new_buffers_initialized(&self, device: &::wgpu::Device) -> Self::Buffers {
+                use ::wgpu::util::DeviceExt;
+                #buffers_name {
+                    #(#binding_idents: {
+                        let mut buffer = #encase_buffers::new(::std::vec::Vec::<u8>::new());
+                        buffer.write(&self.#binding_idents).unwrap();
+                        device.create_buffer_init(&::wgpu::util::BufferInitDescriptor {
+                            label: None,
+                            contents: buffer.as_ref(),
+                            usage: #buffer_usages,
+                        })
+                    },)*
+                }
+            }
+
+            fn write_buffers(&self, queue: &::wgpu::Queue, buffers: &mut Self::Buffers) {
+                #(::encase::internal::WriteInto::write_into(
+                    &self.#binding_idents,
+                    &mut ::encase::internal::Writer::new(
+                        &self.#binding_idents,
+                        &mut *queue
+                            .write_buffer_with(
+                                &buffers.#binding_idents,
+                                0,
+                                ::encase::ShaderType::size(&self.#binding_idents),
+                            )
+                            .unwrap(),
+                        0,
+                    )
+                    .unwrap(),
+                );)*
+            }
+        }
+    }
+}
+
+pub(crate) struct IncludeWgsl {
+    path: syn::LitStr,
+    group_tys: Vec<syn::Type>,
+}
+
+impl syn::parse::Parse for IncludeWgsl {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        // "src/shaders/shader.wgsl", Group0ShaderTypes, Group1ShaderTypes
+        let path = input.parse()?;
+        let mut group_tys = Vec::new();
+        while !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            group_tys.push(input.parse()?);
+        }
+        Ok(Self { path, group_tys })
+    }
+}
+
+fn naga_type_string(module: &naga::Module, ty: naga::Handle<naga::Type>) -> String {
+    let scalar_string = |scalar: naga::Scalar| match (scalar.kind, scalar.width) {
+        (naga::ScalarKind::Bool, _) => "bool".to_string(),
+        (naga::ScalarKind::Sint, 4) => "i32".to_string(),
+        (naga::ScalarKind::Uint, 4) => "u32".to_string(),
+        (naga::ScalarKind::Float, 2) => "f16".to_string(),
+        (naga::ScalarKind::Float, 4) => "f32".to_string(),
+        (kind, width) => format!("{kind:?}{}", width * 8),
+    };
+    let ty = &module.types[ty];
+    match &ty.inner {
+        naga::TypeInner::Scalar(scalar) => scalar_string(*scalar),
+        naga::TypeInner::Vector { size, scalar } => {
+            format!("vec{}<{}>", *size as u8, scalar_string(*scalar))
+        }
+        naga::TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => format!(
+            "mat{}x{}<{}>",
+            *columns as u8,
+            *rows as u8,
+            scalar_string(*scalar)
+        ),
+        naga::TypeInner::Array {
+            base,
+            size: naga::ArraySize::Dynamic,
+            ..
+        } => format!("array<{}>", naga_type_string(module, *base)),
+        naga::TypeInner::Array {
+            base,
+            size: naga::ArraySize::Constant(count),
+            ..
+        } => format!("array<{},{count}>", naga_type_string(module, *base)),
+        _ => ty.name.clone().unwrap_or_else(|| format!("{:?}", ty.inner)),
+    }
+}
+
+// Asserts that `members` matches the struct behind `ty`, if any, recursing into the members
+// which are structs or arrays of structs themselves.
+fn member_checks(
+    module: &naga::Module,
+    ty: naga::Handle<naga::Type>,
+    members: proc_macro2::TokenStream,
+    location: &str,
+) -> proc_macro2::TokenStream {
+    let member_struct_ty = match &module.types[ty].inner {
+        naga::TypeInner::Array { base, .. } => *base,
+        _ => ty,
+    };
+    let naga::TypeInner::Struct {
+        members: naga_members,
+        ..
+    } = &module.types[member_struct_ty].inner
+    else {
+        return quote::quote! {};
+    };
+    let members_len = naga_members.len();
+    let struct_name = naga_type_string(module, member_struct_ty);
+    let len_message = format!(
+        "struct `{struct_name}` of {location} has {members_len} members in WGSL, \
+        which does not match the Rust struct"
+    );
+    let checks = naga_members.iter().enumerate().map(|(index, member)| {
+        let member_name = member.name.clone().unwrap_or_default();
+        let member_ty = naga_type_string(module, member.ty);
+        let member_offset = u64::from(member.offset);
+        let member_message = format!(
+            "member `{member_name}: {member_ty}` at offset {member_offset} of struct \
+            `{struct_name}` of {location} does not match the Rust struct"
+        );
+        let nested_checks = member_checks(
+            module,
+            member.ty,
+            quote::quote! { #members[#index].members },
+            &format!("member `{member_name}` of struct `{struct_name}` of {location}"),
+        );
+        quote::quote! {
+            assert!(
+                #members[#index].matches(#member_name, #member_ty, #member_offset),
+                #member_message,
+            );
+            #nested_checks
+        }
+    });
+    quote::quote! {
+        assert!(#members.len() == #members_len, #len_message);
+        #(#checks)*
+    }
+}
+
+// Parses and validates the WGSL source with naga while compiling, then asserts in const context
+// that every `@group(g) @binding(b)` matches the binding `b` of the `g`-th listed shader types,
+// including the member offsets of struct types and of the structs nested in them, and that
+// every binding of the shader types appears in the source.
+// Expands to the source as a `Cow<'static, str>`.
+//
+// External crate dependencies:
+// - morphing_core
+pub(crate) fn include_wgsl(include_wgsl: IncludeWgsl) -> proc_macro2::TokenStream {
+    let IncludeWgsl { path, group_tys } = &include_wgsl;
+    let full_path = std::path::Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join(path.value());
+    let source = match std::fs::read_to_string(&full_path) {
+        Ok(source) => source,
+        Err(error) => {
+            return syn::Error::new(
+                path.span(),
+                format!("failed to read `{}`: {error}", full_path.display()),
+            )
+            .into_compile_error();
+        }
+    };
+    let module = match naga::front::wgsl::parse_str(&source) {
+        Ok(module) => module,
+        Err(error) => {
+            return syn::Error::new(path.span(), error.emit_to_string(&source))
+                .into_compile_error();
+        }
+    };
+    if let Err(error) = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    {
+        return syn::Error::new(path.span(), error.emit_to_string(&source)).into_compile_error();
+    }
+
+    let file_name = path.value();
+    let mut errors = Vec::new();
+    let mut checks = Vec::new();
+    let mut group_bindings = vec![Vec::new(); group_tys.len()];
+    for (_, global_variable) in module.global_variables.iter() {
+        let Some(resource_binding) = &global_variable.binding else {
+            continue;
+        };
+        let name = global_variable.name.clone().unwrap_or_default();
+        let naga::ResourceBinding { group, binding } = *resource_binding;
+        let Some(group_ty) = group_tys.get(group as usize) else {
+            errors.push(syn::Error::new(
+                path.span(),
+                format!("`{name}` in {file_name} is placed in @group({group}), but no shader types are given for it"),
+            ));
+            continue;
+        };
+        group_bindings[group as usize].push(binding);
+        let address_space = match global_variable.space {
+            naga::AddressSpace::Uniform => "uniform",
+            naga::AddressSpace::Storage { .. } => "storage",
+            space => {
+                errors.push(syn::Error::new(
+                    path.span(),
+                    format!("`{name}` in {file_name} has unsupported address space {space:?}"),
+                ));
+                continue;
+            }
+        };
+        let ty = naga_type_string(&module, global_variable.ty);
+        let size = u64::from(module.types[global_variable.ty].inner.size(module.to_ctx()));
+        let location = format!("`{name}` at @group({group}) @binding({binding}) in {file_name}");
+        let missing_message = format!("{location} is not declared by the Rust shader types");
+        let binding_message = format!(
+            "{location} is declared as `var<{address_space}> {name}: {ty}` of size {size}, \
+            which does not match the Rust shader types"
+        );
+
+        let member_checks = member_checks(
+            &module,
+            global_variable.ty,
+            quote::quote! { binding.members },
+            &location,
+        );
+        checks.push(quote::quote! {
+            const _: () = {
+                let Some(binding) = #root::WgslBinding::find(
+                    <#group_ty as #root::ComponentShaderTypes>::BINDINGS,
+                    #binding,
+                ) else {
+                    panic!(#missing_message);
+                };
+                assert!(
+                    binding.matches(#address_space, #name, #ty, #size),
+                    #binding_message,
+                );
+                #member_checks
+            };
+        });
+    }
+    // Conversely, every Rust binding has to be used by the source.
+    for (group, (group_ty, bindings)) in group_tys.iter().zip(&group_bindings).enumerate() {
+        let message = format!(
+            "`{}` declares a binding which is missing from @group({group}) in {file_name}",
+            quote::quote! { #group_ty }.to_string().replace(' ', ""),
+        );
+        checks.push(quote::quote! {
+            const _: () = {
+                if #root::WgslBinding::find_missing(
+                    <#group_ty as #root::ComponentShaderTypes>::BINDINGS,
+                    &[#(#bindings),*],
+                )
+                .is_some()
+                {
+                    panic!(#message);
+                }
+            };
+        });
+    }
+    if !errors.is_empty() {
+        return errors
+            .into_iter()
+            .map(syn::Error::into_compile_error)
+            .collect();
+    }
+
+    let full_path = full_path.display().to_string();
+    quote::quote! {
+        {
+            #(#checks)*
+            ::std::borrow::Cow::<'static, str>::Borrowed(::std::include_str!(#full_path))
+        }
+    }
+}
+
+#[cfg(test)]
+mod wgpu_tests {
+    use super::*;
+
+    #[test]
+    fn test_include_wgsl_checks() {
+        let path =
+            std::env::temp_dir().join(format!("morphing-nested-{}.wgsl", std::process::id()));
+        std::fs::write(
+            &path,
+            "struct Inner { value: f32, color: vec4<f32> }\n\
+            struct Outer { scale: f32, inner: Inner }\n\
+            @group(0) @binding(0) var<uniform> u_outer: Outer;\n",
+        )
+        .unwrap();
+        let path_str = path.to_str().unwrap();
+        let expanded =
+            include_wgsl(syn::parse_quote! { #path_str, OuterShaderTypes, EmptyShaderTypes })
+                .to_string();
+        std::fs::remove_file(&path).unwrap();
+
+        // `inner` is at offset 16 by the alignment of `vec4<f32>`, and its members are checked too.
+        assert!(
+            expanded
+                .contains(r#"binding . members [1usize] . matches ("inner" , "Inner" , 16u64)"#)
+        );
+        assert!(expanded.contains("binding . members [1usize] . members . len () == 2usize"));
+        assert!(expanded.contains(
+            r#"binding . members [1usize] . members [1usize] . matches ("color" , "vec4<f32>" , 16u64)"#
+        ));
+        // Both groups are checked for Rust bindings missing from the source.
+        assert!(expanded.contains(
+            "< OuterShaderTypes as :: morphing :: ComponentShaderTypes > :: BINDINGS , & [0u32]"
+        ));
+        assert!(expanded.contains(
+            "< EmptyShaderTypes as :: morphing :: ComponentShaderTypes > :: BINDINGS , & []"
+        ));
+    }
+}